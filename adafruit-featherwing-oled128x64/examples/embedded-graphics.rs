@@ -14,7 +14,7 @@ use embedded_graphics::{
 
 mod bsp;
 
-use adafruit_featherwing_oled128x64::{BufferedDisplay, DisplayState, PAGE};
+use adafruit_featherwing_oled128x64::{BufferedDisplay, DisplayState, Error, Fixed, PAGE};
 use defmt_rtt as _;
 
 const ADDRESS: bsp::SevenBitAddress = 0x3C;
@@ -22,12 +22,12 @@ const ADDRESS: bsp::SevenBitAddress = 0x3C;
 async fn demo(
     timer: &bsp::Timer,
     i2c_bus: bsp::I2CPeriph,
-) -> Result<(), <bsp::I2CPeriph as embedded_hal::i2c::ErrorType>::Error> {
+) -> Result<(), Error<<bsp::I2CPeriph as embedded_hal::i2c::ErrorType>::Error>> {
     use bsp::{timed, wait_for};
 
     wait_for(timer, 1_000_000).await;
 
-    let mut display: BufferedDisplay<_, ADDRESS> =
+    let mut display: BufferedDisplay<_, Fixed<ADDRESS>> =
         timed("Init", timer, async { BufferedDisplay::new(i2c_bus).await })
             .await
             .map_err(|(_, e)| e)?;
@@ -3,7 +3,7 @@
 
 mod bsp;
 
-use adafruit_featherwing_oled128x64::{Destination, Display, DisplayState};
+use adafruit_featherwing_oled128x64::{Destination, Display, DisplayState, Error, Fixed};
 use defmt_rtt as _;
 
 const ADDRESS: bsp::SevenBitAddress = 0x3C;
@@ -30,12 +30,12 @@ const GLYPHS: [u8; 1024] = {
 async fn demo(
     timer: &bsp::Timer,
     i2c_bus: bsp::I2CPeriph,
-) -> Result<(), <bsp::I2CPeriph as embedded_hal::i2c::ErrorType>::Error> {
+) -> Result<(), Error<<bsp::I2CPeriph as embedded_hal::i2c::ErrorType>::Error>> {
     use bsp::{timed, wait_for};
 
     wait_for(timer, 1_000_000).await;
 
-    let mut display: Display<_, ADDRESS> = timed("Init", timer, async {
+    let mut display: Display<_, Fixed<ADDRESS>> = timed("Init", timer, async {
         Display::new(i2c_bus).await.map_err(|(_, e)| e)
     })
     .await?;
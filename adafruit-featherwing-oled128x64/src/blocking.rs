@@ -0,0 +1,416 @@
+//! Blocking API, for use without an executor.
+//!
+//! These types wrap their async counterparts over a [`Blocking`] bus and drive them with
+//! [`block_on`], sharing the initialisation sequence and the flush algorithm.
+
+use core::num::NonZeroUsize;
+use core::ops::Range;
+
+use embedded_hal_async::i2c::SevenBitAddress;
+use sh1107::blocking::{block_on, Blocking, DelayNs, WriteIter};
+use sh1107::retry::{NoRetry, RetryPolicy};
+use sh1107::wait::{NoWait, WaitReady};
+use sh1107::Address;
+
+use crate::{Destination, DisplayState, Error, Fixed, Registers, Status};
+
+/// Blocking counterpart of [`crate::Display`].
+pub struct Display<T, A, R = NoRetry, W = NoWait>(crate::Display<Blocking<T>, A, R, W>);
+
+impl<T, const ADDRESS: SevenBitAddress> Display<T, Fixed<ADDRESS>>
+where
+    T: WriteIter<SevenBitAddress>,
+{
+    pub fn new(i2c_bus: T) -> Result<Self, (T, Error<T::Error>)> {
+        block_on(crate::Display::new(Blocking::new(i2c_bus)))
+            .map(Self)
+            .map_err(|(i2c_bus, e)| (i2c_bus.into_inner(), e))
+    }
+
+    /// See [`crate::Display::power_up`].
+    pub fn power_up(i2c_bus: T, delay: &mut impl DelayNs) -> Result<Self, (T, Error<T::Error>)> {
+        let mut delay = Blocking::new(delay);
+        block_on(crate::Display::power_up(Blocking::new(i2c_bus), &mut delay))
+            .map(Self)
+            .map_err(|(i2c_bus, e)| (i2c_bus.into_inner(), e))
+    }
+
+    /// See [`crate::Display::power_up_with_reset`].
+    pub fn power_up_with_reset(
+        i2c_bus: T,
+        reset: &mut impl embedded_hal::digital::OutputPin,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        let mut delay = Blocking::new(delay);
+        block_on(crate::Display::power_up_with_reset(
+            Blocking::new(i2c_bus),
+            reset,
+            &mut delay,
+        ))
+        .map(Self)
+        .map_err(|(i2c_bus, e)| (i2c_bus.into_inner(), e))
+    }
+}
+
+impl<T> Display<T, SevenBitAddress>
+where
+    T: WriteIter<SevenBitAddress>,
+{
+    pub fn with_address(
+        i2c_bus: T,
+        address: SevenBitAddress,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        block_on(crate::Display::with_address(
+            Blocking::new(i2c_bus),
+            address,
+        ))
+        .map(Self)
+        .map_err(|(i2c_bus, e)| (i2c_bus.into_inner(), e))
+    }
+
+    /// Initialises the display found at either of the addresses selectable with the SA0 jumper.
+    pub fn probe(i2c_bus: T) -> Result<Self, (T, Error<T::Error>)> {
+        block_on(crate::Display::probe(Blocking::new(i2c_bus)))
+            .map(Self)
+            .map_err(|(i2c_bus, e)| (i2c_bus.into_inner(), e))
+    }
+
+    /// See [`crate::Display::power_up_with_address`].
+    pub fn power_up_with_address(
+        i2c_bus: T,
+        address: SevenBitAddress,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        let mut delay = Blocking::new(delay);
+        block_on(crate::Display::power_up_with_address(
+            Blocking::new(i2c_bus),
+            address,
+            &mut delay,
+        ))
+        .map(Self)
+        .map_err(|(i2c_bus, e)| (i2c_bus.into_inner(), e))
+    }
+
+    /// See [`crate::Display::power_up_with_address_and_reset`].
+    pub fn power_up_with_address_and_reset(
+        i2c_bus: T,
+        address: SevenBitAddress,
+        reset: &mut impl embedded_hal::digital::OutputPin,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        let mut delay = Blocking::new(delay);
+        block_on(crate::Display::power_up_with_address_and_reset(
+            Blocking::new(i2c_bus),
+            address,
+            reset,
+            &mut delay,
+        ))
+        .map(Self)
+        .map_err(|(i2c_bus, e)| (i2c_bus.into_inner(), e))
+    }
+}
+
+impl<T, A, R, W> Display<T, A, R, W>
+where
+    T: WriteIter<SevenBitAddress>,
+    A: Address,
+    R: RetryPolicy,
+    W: WaitReady,
+{
+    /// See [`crate::Display::with_retry`], blocking delays are used through [`Blocking`].
+    pub fn with_retry<R2: RetryPolicy>(self, retry: R2) -> Display<T, A, R2, W> {
+        Display(self.0.with_retry(retry))
+    }
+    pub fn retry_policy_mut(&mut self) -> &mut R {
+        self.0.retry_policy_mut()
+    }
+    /// See [`crate::Display::with_ready_wait`], blocking delays are used through [`Blocking`].
+    pub fn with_ready_wait<W2: WaitReady>(self, wait: W2) -> Display<T, A, R, W2> {
+        Display(self.0.with_ready_wait(wait))
+    }
+    pub fn ready_wait_mut(&mut self) -> &mut W {
+        self.0.ready_wait_mut()
+    }
+
+    pub fn address(&self) -> SevenBitAddress {
+        self.0.address()
+    }
+    /// See [`sh1107::Sh1107::set_max_transfer`].
+    pub fn set_max_transfer(&mut self, max_transfer: Option<NonZeroUsize>) {
+        self.0.set_max_transfer(max_transfer)
+    }
+    /// See [`crate::Display::registers`].
+    pub fn registers(&self) -> &Registers {
+        self.0.registers()
+    }
+    /// See [`sh1107::Sh1107::invalidate_registers`].
+    pub fn invalidate_registers(&mut self) {
+        self.0.invalidate_registers()
+    }
+    pub fn contrast(&self) -> Option<u8> {
+        self.0.contrast()
+    }
+    pub fn start_line(&self) -> Option<u8> {
+        self.0.start_line()
+    }
+    /// See [`crate::Display::is_flipped_horizontal`].
+    pub fn is_flipped_horizontal(&self) -> Option<bool> {
+        self.0.is_flipped_horizontal()
+    }
+    /// See [`crate::Display::is_flipped_vertical`].
+    pub fn is_flipped_vertical(&self) -> Option<bool> {
+        self.0.is_flipped_vertical()
+    }
+    pub fn set_state(&mut self, state: DisplayState) -> Result<(), Error<T::Error>> {
+        block_on(self.0.set_state(state))
+    }
+    pub fn sleep(&mut self) -> Result<(), Error<T::Error>> {
+        block_on(self.0.sleep())
+    }
+    pub fn wake(&mut self) -> Result<(), Error<T::Error>> {
+        block_on(self.0.wake())
+    }
+    pub fn is_asleep(&self) -> bool {
+        self.0.is_asleep()
+    }
+    pub fn set_start_line(&mut self, line: u8) -> Result<(), Error<T::Error>> {
+        block_on(self.0.set_start_line(line))
+    }
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), Error<T::Error>> {
+        block_on(self.0.set_contrast(contrast))
+    }
+    pub fn frame_rate_millihertz(&self) -> Option<u32> {
+        self.0.frame_rate_millihertz()
+    }
+    pub fn set_frame_rate(&mut self, millihertz: u32) -> Result<u32, Error<T::Error>> {
+        block_on(self.0.set_frame_rate(millihertz))
+    }
+    pub fn flip_horizontal(&mut self, flip: bool) -> Result<(), Error<T::Error>> {
+        block_on(self.0.flip_horizontal(flip))
+    }
+    pub fn band(&self) -> Option<Range<u8>> {
+        self.0.band()
+    }
+    pub fn set_band(&mut self, band: Range<u8>) -> Result<(), Error<T::Error>> {
+        block_on(self.0.set_band(band))
+    }
+    pub fn clear_band(&mut self) -> Result<(), Error<T::Error>> {
+        block_on(self.0.clear_band())
+    }
+    pub fn flip_vertical(&mut self, flip: bool) -> Result<(), Error<T::Error>> {
+        block_on(self.0.flip_vertical(flip))
+    }
+
+    pub fn write_frame_by_column(
+        &mut self,
+        dest: Destination,
+        buf: impl Iterator<Item = u8>,
+    ) -> Result<(), Error<T::Error>> {
+        block_on(self.0.write_frame_by_column(dest, buf))
+    }
+    pub fn write_frame_by_page(
+        &mut self,
+        dest: Destination,
+        buf: impl Iterator<Item = u8>,
+    ) -> Result<(), Error<T::Error>> {
+        block_on(self.0.write_frame_by_page(dest, buf))
+    }
+    /// See [`crate::Display::read_frame`].
+    pub fn read_frame(&mut self, buf: &mut [u8]) -> Result<(), Error<T::Error>> {
+        block_on(self.0.read_frame(buf))
+    }
+    /// See [`crate::Display::read_frame_by_column`].
+    pub fn read_frame_by_column(&mut self, buf: &mut [u8]) -> Result<(), Error<T::Error>> {
+        block_on(self.0.read_frame_by_column(buf))
+    }
+
+    /// See [`crate::Display::set_pixel`].
+    pub fn set_pixel(
+        &mut self,
+        dest: Destination,
+        x: u8,
+        y: u8,
+        on: bool,
+    ) -> Result<(), Error<T::Error>> {
+        block_on(self.0.set_pixel(dest, x, y, on))
+    }
+
+    pub fn status(&mut self) -> Result<Status, Error<T::Error>> {
+        block_on(self.0.status())
+    }
+
+    pub fn is_busy(&mut self) -> Result<bool, Error<T::Error>> {
+        block_on(self.0.is_busy())
+    }
+
+    /// See [`crate::Display::verify_state`].
+    pub fn verify_state(&mut self, state: DisplayState) -> Result<(), Error<T::Error>> {
+        block_on(self.0.verify_state(state))
+    }
+
+    pub fn wait_while_busy(&mut self) -> Result<(), Error<T::Error>> {
+        block_on(self.0.wait_while_busy())
+    }
+    /// See [`sh1107::Sh1107::wait_until_ready`].
+    pub fn wait_until_ready(
+        &mut self,
+        delay: &mut impl DelayNs,
+        interval_us: u32,
+        timeout_us: u32,
+    ) -> Result<(), Error<T::Error>> {
+        let mut delay = Blocking::new(delay);
+        block_on(self.0.wait_until_ready(&mut delay, interval_us, timeout_us))
+    }
+
+    pub fn release(self) -> T {
+        self.0.release().into_inner()
+    }
+}
+
+#[cfg(feature = "embedded-graphics")]
+pub use self::embedded_graphics::BufferedDisplay;
+
+#[cfg(feature = "embedded-graphics")]
+mod embedded_graphics {
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::prelude::*;
+    use sh1107::blocking::{block_on, DelayNs, WriteIter};
+    use sh1107::retry::{NoRetry, RetryPolicy};
+    use sh1107::wait::{NoWait, WaitReady};
+    use sh1107::Address;
+
+    use super::{Display, SevenBitAddress};
+    use crate::embedded_graphics::Framebuffer;
+    use crate::{Destination, Error, Fixed};
+
+    /// Blocking counterpart of [`crate::BufferedDisplay`].
+    pub struct BufferedDisplay<T, A, R = NoRetry, W = NoWait> {
+        display: Display<T, A, R, W>,
+        framebuffer: Framebuffer,
+    }
+    impl<T: WriteIter<SevenBitAddress>, A: Address, R: RetryPolicy, W: WaitReady> core::ops::Deref
+        for BufferedDisplay<T, A, R, W>
+    {
+        type Target = Display<T, A, R, W>;
+        fn deref(&self) -> &Display<T, A, R, W> {
+            &self.display
+        }
+    }
+    impl<T: WriteIter<SevenBitAddress>, A: Address, R: RetryPolicy, W: WaitReady>
+        core::ops::DerefMut for BufferedDisplay<T, A, R, W>
+    {
+        fn deref_mut(&mut self) -> &mut Display<T, A, R, W> {
+            &mut self.display
+        }
+    }
+    impl<T: WriteIter<SevenBitAddress>, const ADDRESS: SevenBitAddress>
+        BufferedDisplay<T, Fixed<ADDRESS>>
+    {
+        pub fn new(i2c_bus: T) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(Display::new(i2c_bus)?))
+        }
+        /// See [`crate::Display::power_up`].
+        pub fn power_up(
+            i2c_bus: T,
+            delay: &mut impl DelayNs,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(Display::power_up(i2c_bus, delay)?))
+        }
+        /// See [`crate::Display::power_up_with_reset`].
+        pub fn power_up_with_reset(
+            i2c_bus: T,
+            reset: &mut impl embedded_hal::digital::OutputPin,
+            delay: &mut impl DelayNs,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(Display::power_up_with_reset(
+                i2c_bus, reset, delay,
+            )?))
+        }
+    }
+    impl<T: WriteIter<SevenBitAddress>> BufferedDisplay<T, SevenBitAddress> {
+        pub fn with_address(
+            i2c_bus: T,
+            address: SevenBitAddress,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(Display::with_address(i2c_bus, address)?))
+        }
+        pub fn probe(i2c_bus: T) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(Display::probe(i2c_bus)?))
+        }
+        /// See [`crate::Display::power_up_with_address`].
+        pub fn power_up_with_address(
+            i2c_bus: T,
+            address: SevenBitAddress,
+            delay: &mut impl DelayNs,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(Display::power_up_with_address(
+                i2c_bus, address, delay,
+            )?))
+        }
+        /// See [`crate::Display::power_up_with_address_and_reset`].
+        pub fn power_up_with_address_and_reset(
+            i2c_bus: T,
+            address: SevenBitAddress,
+            reset: &mut impl embedded_hal::digital::OutputPin,
+            delay: &mut impl DelayNs,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(
+                Display::power_up_with_address_and_reset(i2c_bus, address, reset, delay)?,
+            ))
+        }
+    }
+    impl<T, A, R, W> BufferedDisplay<T, A, R, W>
+    where
+        T: WriteIter<SevenBitAddress>,
+        A: Address,
+        R: RetryPolicy,
+        W: WaitReady,
+    {
+        fn from_display(display: Display<T, A, R, W>) -> Self {
+            Self {
+                display,
+                framebuffer: Framebuffer::new(),
+            }
+        }
+        /// See [`Display::with_retry`].
+        pub fn with_retry<R2: RetryPolicy>(self, retry: R2) -> BufferedDisplay<T, A, R2, W> {
+            BufferedDisplay {
+                display: self.display.with_retry(retry),
+                framebuffer: self.framebuffer,
+            }
+        }
+        /// See [`Display::with_ready_wait`], the wait also applies to [`Self::flush`].
+        pub fn with_ready_wait<W2: WaitReady>(self, wait: W2) -> BufferedDisplay<T, A, R, W2> {
+            BufferedDisplay {
+                display: self.display.with_ready_wait(wait),
+                framebuffer: self.framebuffer,
+            }
+        }
+        pub fn flush(&mut self) -> Result<(), Error<T::Error>> {
+            self.flush_to(Destination::Frame1)
+        }
+        pub fn flush_to(&mut self, destination: Destination) -> Result<(), Error<T::Error>> {
+            block_on(self.framebuffer.flush_to(&mut self.display.0, destination))
+        }
+    }
+
+    impl<T, A, R, W> OriginDimensions for BufferedDisplay<T, A, R, W> {
+        fn size(&self) -> Size {
+            Size::new(64, 128)
+        }
+    }
+
+    impl<T, A, R, W> DrawTarget for BufferedDisplay<T, A, R, W> {
+        type Color = BinaryColor;
+
+        type Error = core::convert::Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            self.framebuffer.draw_iter(pixels);
+            Ok(())
+        }
+    }
+}
@@ -1,91 +1,404 @@
 #![no_std]
 
+use core::num::NonZeroUsize;
+use core::ops::Range;
+
+use embedded_hal::digital::OutputPin;
+
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::i2c::SevenBitAddress;
+use sh1107::retry::{NoRetry, RetryPolicy};
+use sh1107::wait::{NoWait, WaitReady};
 use sh1107::Direction;
-use sh1107::{AddressMode, Sh1107};
-use sh1107::{Command, DisplayMode};
+use sh1107::{Address, AddressMode, I2cInterface, Sh1107};
+use sh1107::{ChargePeriod, DcDcFrequency, DcDcSettings, OscFrequency, VcomhLevel};
+use sh1107::{Command, DisplayMode, FrameTiming};
 
 pub use sh1107::DisplayState;
+pub use sh1107::Error;
+pub use sh1107::Fixed;
+pub use sh1107::Registers;
+pub use sh1107::Status;
+
+pub mod blocking;
 
 pub const COLUMN: u8 = 64;
 pub const ROW: u8 = 128;
 pub const PAGE: u8 = ROW / 8;
 
+/// Time given to VDD and VPP to settle before the controller is initialised, see
+/// [`Display::power_up`].
+pub const SUPPLY_SETTLE_MS: u32 = 10;
+/// Time the panel takes to light up once turned on, from the datasheet.
+pub const DISPLAY_ON_MS: u32 = 100;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-#[cfg_attr(feature = "defmt", defmt::Format)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Destination {
     Frame1,
     Frame2,
 }
 
-pub struct Display<T, const ADDRESS: SevenBitAddress>(Sh1107<T, ADDRESS>);
+/// Timing registers set by [`INIT_SEQUENCE`].
+const INIT_TIMING: FrameTiming = FrameTiming {
+    divider: 2,
+    osc_freq: OscFrequency::Nominal,
+    multiplex_ratio: COLUMN,
+    precharge: Some(ChargePeriod::DEFAULT),
+    discharge: ChargePeriod::DEFAULT,
+};
+
+/// Display offset putting the rows of `band` on their COM lines.
+///
+/// The 64 COM lines of the panel are wired from COM96, wrapping around to COM31.
+const fn display_offset(direction: Direction, band: &Range<u8>) -> u8 {
+    match direction {
+        Direction::Normal => (96 + band.start) % 128,
+        Direction::Inverted => (96 + 128 - band.end) % 128,
+    }
+}
+
+/// Rows driven with `multiplex_ratio` and `offset`, the inverse of [`display_offset`].
+fn band(direction: Direction, multiplex_ratio: u8, offset: u8) -> Option<Range<u8>> {
+    let start = match direction {
+        Direction::Normal => offset.wrapping_sub(96) % 128,
+        Direction::Inverted => (96u8.wrapping_sub(offset) % 128).checked_sub(multiplex_ratio)?,
+    };
+    let end = start
+        .checked_add(multiplex_ratio)
+        .filter(|&end| end <= COLUMN)?;
+    Some(start..end)
+}
+
+/// Row of the panel scanned first in `band`: its top row, or its bottom one when inverted.
+///
+/// The start line is the RAM line shown on that row, counted from the top of the panel.
+const fn first_scanned_row(direction: Direction, band: &Range<u8>) -> u8 {
+    match direction {
+        Direction::Normal => band.start,
+        Direction::Inverted => COLUMN - band.end,
+    }
+}
+
+/// Start line showing in `band` the lines shown there with all rows driven from `base_line`.
+const fn start_line(base_line: u8, direction: Direction, band: &Range<u8>) -> u8 {
+    (base_line + first_scanned_row(direction, band)) % 128
+}
+
+/// Start line all rows would be driven from, the inverse of [`start_line`].
+const fn base_line(start_line: u8, direction: Direction, band: &Range<u8>) -> u8 {
+    start_line.wrapping_sub(first_scanned_row(direction, band)) % 128
+}
 
-impl<T, const ADDRESS: SevenBitAddress> Display<T, ADDRESS>
+const INIT_SEQUENCE: &[u8] = {
+    use Command::*;
+    sh1107::encode_commands![
+        DisplayOnOff(DisplayState::Off),
+        SetClkDividerOscFrequency {
+            divider: 2, // divide by 2
+            osc_freq: OscFrequency::Nominal,
+        },
+        SetMultiplexRatio(COLUMN),
+        // rendering alignment
+        SetDisplayOffset(96),
+        SetStartLine(0),
+        // display orientation
+        SetSegmentReMap(false),
+        SetCOMScanDirection(Direction::Normal),
+        // electrical configuration
+        SetChargePeriods {
+            precharge: Some(ChargePeriod::DEFAULT), // 2 DCLKs
+            discharge: ChargePeriod::DEFAULT,       // 2 DCLKs
+        },
+        SetVCOMHDeselectLevel(VcomhLevel::DEFAULT), // 0.770 x VCC
+        SetDCDCSettings(DcDcSettings {
+            enabled: true,
+            frequency: DcDcFrequency::MAX, // 130% of nominal
+        }),
+        // intensity
+        SetContrastControl(128), // 110 / 256
+        ForceEntireDisplay(false),
+        // display & addressing mode
+        SetDisplayMode(DisplayMode::BlackOnWhite),
+    ]
+};
+
+/// `A` is either [`Fixed`] or a runtime [`SevenBitAddress`], see [`sh1107::Address`].
+///
+/// `R` decides whether failed transactions are retried, see [`Self::with_retry`].
+///
+/// `W` decides whether frame writes first wait for the controller to be ready, see
+/// [`Self::with_ready_wait`].
+pub struct Display<T, A, R = NoRetry, W = NoWait>(Sh1107<I2cInterface<T, A>, R>, W);
+
+impl<T, const ADDRESS: SevenBitAddress> Display<T, Fixed<ADDRESS>>
 where
     T: sh1107::WriteIter<SevenBitAddress>,
 {
-    pub async fn new(i2c_bus: T) -> Result<Self, (T, T::Error)> {
-        let mut sh1107 = Sh1107::new(i2c_bus);
-
-        use Command::*;
-        let init_sequence = [
-            DisplayOnOff(DisplayState::Off),
-            SetClkDividerOscFrequency {
-                divider: 2,        // divide by 2
-                osc_freq_ratio: 0, // +0%
-            },
-            SetMultiplexRatio(COLUMN),
-            // rendering alignment
-            SetDisplayOffset(96),
-            SetStartLine(0),
-            // display orientation
-            SetSegmentReMap(false),
-            SetCOMScanDirection(Direction::Normal),
-            // electrical configuration
-            SetChargePeriods {
-                precharge: Some(2),
-                discharge: 2,
-            },
-            SetVCOMHDeselectLevel(0x35),
-            SetDCDCSettings(0xF),
-            // intensity
-            SetContrastControl(128), // 110 / 256
-            ForceEntireDisplay(false),
-            // display & addressing mode
-            SetDisplayMode(DisplayMode::BlackOnWhite),
-        ];
-
-        match sh1107.run(init_sequence).await {
+    pub async fn new(i2c_bus: T) -> Result<Self, (T, Error<T::Error>)> {
+        Self::init(Sh1107::new(I2cInterface::new(i2c_bus))).await
+    }
+
+    /// Brings the display up following the power-up sequence of the datasheet:
+    ///
+    /// 1. waits [`SUPPLY_SETTLE_MS`] for the supplies to settle,
+    /// 2. sends the initialisation sequence,
+    /// 3. clears the display RAM,
+    /// 4. turns the display on and waits [`DISPLAY_ON_MS`] for the panel to light up.
+    ///
+    /// Unlike [`Self::new`], the display is left on.
+    pub async fn power_up(
+        i2c_bus: T,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        Self::bring_up(Sh1107::new(I2cInterface::new(i2c_bus)), delay).await
+    }
+
+    /// Same as [`Self::power_up`], starting with a pulse on the RES pin of the controller.
+    pub async fn power_up_with_reset(
+        i2c_bus: T,
+        reset: &mut impl OutputPin,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        Self::reset_and_bring_up(Sh1107::new(I2cInterface::new(i2c_bus)), reset, delay).await
+    }
+}
+
+impl<T> Display<T, SevenBitAddress>
+where
+    T: sh1107::WriteIter<SevenBitAddress>,
+{
+    pub async fn with_address(
+        i2c_bus: T,
+        address: SevenBitAddress,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        Self::init(Sh1107::new(I2cInterface::with_address(i2c_bus, address))).await
+    }
+
+    /// Initialises the display found at either of the addresses selectable with the SA0 jumper.
+    pub async fn probe(i2c_bus: T) -> Result<Self, (T, Error<T::Error>)> {
+        Self::init(Sh1107::probe(i2c_bus).await?).await
+    }
+
+    /// See [`Display::power_up`].
+    pub async fn power_up_with_address(
+        i2c_bus: T,
+        address: SevenBitAddress,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        let interface = I2cInterface::with_address(i2c_bus, address);
+        Self::bring_up(Sh1107::new(interface), delay).await
+    }
+
+    /// See [`Display::power_up_with_reset`].
+    pub async fn power_up_with_address_and_reset(
+        i2c_bus: T,
+        address: SevenBitAddress,
+        reset: &mut impl OutputPin,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        let interface = I2cInterface::with_address(i2c_bus, address);
+        Self::reset_and_bring_up(Sh1107::new(interface), reset, delay).await
+    }
+}
+
+impl<T, A> Display<T, A>
+where
+    T: sh1107::WriteIter<SevenBitAddress>,
+    A: Address,
+{
+    async fn init(mut sh1107: Sh1107<I2cInterface<T, A>>) -> Result<Self, (T, Error<T::Error>)> {
+        match sh1107.run_encoded(INIT_SEQUENCE).await {
             Ok(_) => {}
-            Err(e) => return Err((sh1107.release(), e)),
+            Err(e) => return Err((sh1107.release().release(), e)),
+        }
+
+        Ok(Display(sh1107, NoWait))
+    }
+
+    async fn reset_and_bring_up(
+        mut sh1107: Sh1107<I2cInterface<T, A>>,
+        reset: &mut impl OutputPin,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        match sh1107.reset(reset, delay).await {
+            Ok(()) => Self::bring_up(sh1107, delay).await,
+            Err(e) => Err((sh1107.release().release(), e)),
+        }
+    }
+
+    async fn bring_up(
+        sh1107: Sh1107<I2cInterface<T, A>>,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, (T, Error<T::Error>)> {
+        delay.delay_ms(SUPPLY_SETTLE_MS).await;
+        let mut display = Self::init(sh1107).await?;
+        match display.clear_and_turn_on(delay).await {
+            Ok(()) => Ok(display),
+            Err(e) => Err((display.release(), e)),
+        }
+    }
+
+    async fn clear_and_turn_on(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<T::Error>> {
+        self.0
+            .run([Command::SetAddressMode(AddressMode::Page)])
+            .await?;
+        // both frames
+        for page in 0..PAGE {
+            self.0
+                .run_then_write_to_ram(
+                    [Command::SetColumnAddress(0), Command::SetPageAddress(page)],
+                    core::iter::repeat_n(0, 2 * usize::from(COLUMN)),
+                )
+                .await?;
         }
+        self.set_state(DisplayState::On).await?;
+        delay.delay_ms(DISPLAY_ON_MS).await;
+        Ok(())
+    }
+}
+
+impl<T, A, R, W> Display<T, A, R, W>
+where
+    T: sh1107::WriteIter<SevenBitAddress>,
+    A: Address,
+    R: RetryPolicy,
+    W: WaitReady,
+{
+    /// Retries failed transactions according to `retry`, see [`Sh1107::with_retry`].
+    pub fn with_retry<R2: RetryPolicy>(self, retry: R2) -> Display<T, A, R2, W> {
+        Display(self.0.with_retry(retry), self.1)
+    }
+    pub fn retry_policy_mut(&mut self) -> &mut R {
+        self.0.retry_policy_mut()
+    }
+    /// Waits according to `wait` before each frame write, for instance with a
+    /// [`sh1107::wait::BusyWait`].
+    pub fn with_ready_wait<W2: WaitReady>(self, wait: W2) -> Display<T, A, R, W2> {
+        Display(self.0, wait)
+    }
+    pub fn ready_wait_mut(&mut self) -> &mut W {
+        &mut self.1
+    }
 
-        Ok(Display(sh1107))
+    pub fn address(&self) -> SevenBitAddress {
+        self.0.interface().address()
+    }
+    /// See [`Sh1107::set_max_transfer`].
+    pub fn set_max_transfer(&mut self, max_transfer: Option<NonZeroUsize>) {
+        self.0.set_max_transfer(max_transfer)
+    }
+    /// Configuration set through this driver, see [`Sh1107::registers`].
+    pub fn registers(&self) -> &Registers {
+        self.0.registers()
+    }
+    /// See [`Sh1107::invalidate_registers`].
+    pub fn invalidate_registers(&mut self) {
+        self.0.invalidate_registers()
+    }
+    pub fn contrast(&self) -> Option<u8> {
+        self.0.registers().contrast()
+    }
+    pub fn start_line(&self) -> Option<u8> {
+        self.0.registers().start_line()
     }
-    pub async fn set_state(&mut self, state: DisplayState) -> Result<(), T::Error> {
+    /// Whether the display was flipped with [`Self::flip_horizontal`].
+    pub fn is_flipped_horizontal(&self) -> Option<bool> {
+        let direction = self.0.registers().com_scan_direction()?;
+        Some(direction == Direction::Inverted)
+    }
+    /// Whether the display was flipped with [`Self::flip_vertical`].
+    pub fn is_flipped_vertical(&self) -> Option<bool> {
+        self.0.registers().segment_remap()
+    }
+    pub async fn set_state(&mut self, state: DisplayState) -> Result<(), Error<T::Error>> {
         self.0.run([Command::DisplayOnOff(state)]).await
     }
-    pub async fn set_start_line(&mut self, line: u8) -> Result<(), T::Error> {
+    /// Turns the display and its DC-DC converter off, keeping the display RAM.
+    pub async fn sleep(&mut self) -> Result<(), Error<T::Error>> {
+        self.0.sleep().await
+    }
+    /// Turns the display back on after [`Self::sleep`], restoring its configuration.
+    pub async fn wake(&mut self) -> Result<(), Error<T::Error>> {
+        self.0.wake().await
+    }
+    pub fn is_asleep(&self) -> bool {
+        self.0.is_asleep()
+    }
+    pub async fn set_start_line(&mut self, line: u8) -> Result<(), Error<T::Error>> {
         self.0.run([Command::SetStartLine(line)]).await
     }
-    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), T::Error> {
+    pub async fn set_contrast(&mut self, contrast: u8) -> Result<(), Error<T::Error>> {
         self.0.run([Command::SetContrastControl(contrast)]).await
     }
-    pub async fn flip_horizontal(&mut self, flip: bool) -> Result<(), T::Error> {
-        let commands = if flip {
-            [
-                Command::SetCOMScanDirection(Direction::Inverted),
-                Command::SetDisplayOffset(32),
-            ]
+    /// Estimated frame rate, in thousandths of Hz, see [`FrameTiming`].
+    pub fn frame_rate_millihertz(&self) -> Option<u32> {
+        self.0.registers().frame_timing()?.frame_rate_millihertz()
+    }
+    /// Sets the clock closest to `millihertz`, returning the estimated frame rate reached.
+    pub async fn set_frame_rate(&mut self, millihertz: u32) -> Result<u32, Error<T::Error>> {
+        let timing = self.0.registers().frame_timing().unwrap_or(INIT_TIMING);
+        let timing = timing.closest_to(millihertz).unwrap_or(timing);
+        // rejects an out of range multiplex ratio, the only way for the estimate to fail
+        self.0.run(timing.commands()).await?;
+        Ok(timing.frame_rate_millihertz().unwrap_or_default())
+    }
+    pub async fn flip_horizontal(&mut self, flip: bool) -> Result<(), Error<T::Error>> {
+        let direction = if flip {
+            Direction::Inverted
         } else {
-            [
-                Command::SetCOMScanDirection(Direction::Normal),
-                Command::SetDisplayOffset(96),
-            ]
+            Direction::Normal
         };
-        self.0.run(commands).await
+        let band = self.band().unwrap_or(0..COLUMN);
+        self.0.run(self.layout(direction, band)).await
+    }
+    /// Rows driven by the controller, see [`Self::set_band`].
+    pub fn band(&self) -> Option<Range<u8>> {
+        let registers = self.0.registers();
+        band(
+            registers.com_scan_direction()?,
+            registers.multiplex_ratio()?,
+            registers.display_offset()?,
+        )
+    }
+    /// Only drives the rows in `band`, out of [`COLUMN`], leaving the others dark.
+    ///
+    /// Fewer rows draw less power. The rows keep showing the same content, the start line is moved
+    /// along with the band. Returns [`Error::InvalidArea`] if `band` is empty or goes past
+    /// [`COLUMN`].
+    pub async fn set_band(&mut self, band: Range<u8>) -> Result<(), Error<T::Error>> {
+        if band.is_empty() || band.end > COLUMN {
+            return Err(Error::InvalidArea);
+        }
+        let direction = self
+            .0
+            .registers()
+            .com_scan_direction()
+            .unwrap_or(Direction::Normal);
+        self.0.run(self.layout(direction, band)).await
     }
-    pub async fn flip_vertical(&mut self, flip: bool) -> Result<(), T::Error> {
+    /// Commands scanning `band` in `direction`, keeping the content of the rows.
+    fn layout(&self, direction: Direction, band: Range<u8>) -> [Command; 4] {
+        let registers = self.0.registers();
+        let current_direction = registers.com_scan_direction().unwrap_or(Direction::Normal);
+        let current_band = self.band().unwrap_or(0..COLUMN);
+        let base_line = base_line(
+            registers.start_line().unwrap_or(0),
+            current_direction,
+            &current_band,
+        );
+        [
+            Command::SetCOMScanDirection(direction),
+            Command::SetMultiplexRatio(band.end - band.start),
+            Command::SetDisplayOffset(display_offset(direction, &band)),
+            Command::SetStartLine(start_line(base_line, direction, &band)),
+        ]
+    }
+    /// Drives all the rows again, see [`Self::set_band`].
+    pub async fn clear_band(&mut self) -> Result<(), Error<T::Error>> {
+        self.set_band(0..COLUMN).await
+    }
+    pub async fn flip_vertical(&mut self, flip: bool) -> Result<(), Error<T::Error>> {
         self.0.run([Command::SetSegmentReMap(flip)]).await
     }
 
@@ -93,7 +406,8 @@ where
         &mut self,
         dest: Destination,
         mut buf: impl Iterator<Item = u8>,
-    ) -> Result<(), T::Error> {
+    ) -> Result<(), Error<T::Error>> {
+        self.1.wait_ready(&mut self.0).await?;
         self.0
             .run([Command::SetAddressMode(AddressMode::Column)])
             .await?;
@@ -122,7 +436,8 @@ where
         &mut self,
         dest: Destination,
         mut buf: impl Iterator<Item = u8>,
-    ) -> Result<(), T::Error> {
+    ) -> Result<(), Error<T::Error>> {
+        self.1.wait_ready(&mut self.0).await?;
         self.0
             .run([Command::SetAddressMode(AddressMode::Page)])
             .await?;
@@ -144,34 +459,83 @@ where
         }
         Ok(())
     }
-    pub async fn read_frame(&mut self, buf: &mut [u8]) -> Result<(), T::Error> {
+    /// Reads the first frame page after page, in the layout used by [`Self::write_frame_by_page`].
+    pub async fn read_frame(&mut self, buf: &mut [u8]) -> Result<(), Error<T::Error>> {
         self.0
-            .run([Command::SetAddressMode(AddressMode::Page)])
-            .await?;
-        for page in 0..PAGE {
-            self.0
-                .run([Command::SetColumnAddress(0), Command::SetPageAddress(page)])
-                .await?;
+            .read_region(AddressMode::Page, 0..PAGE, 0..COLUMN, buf)
+            .await
+    }
+    /// Reads the first frame column after column, in the layout used by
+    /// [`Self::write_frame_by_column`].
+    pub async fn read_frame_by_column(&mut self, buf: &mut [u8]) -> Result<(), Error<T::Error>> {
+        self.0
+            .read_region(AddressMode::Column, 0..PAGE, 0..COLUMN, buf)
+            .await
+    }
 
-            let col = usize::from(COLUMN);
-            let start = usize::from(page) * col;
-            let end = start + col - 1;
-            self.0.read_from_ram(&mut buf[start..=end]).await?;
+    /// Turns a single pixel on or off with a read-modify-write of the display RAM, for use without
+    /// a framebuffer.
+    ///
+    /// Pixels out of the display are ignored.
+    pub async fn set_pixel(
+        &mut self,
+        dest: Destination,
+        x: u8,
+        y: u8,
+        on: bool,
+    ) -> Result<(), Error<T::Error>> {
+        if x >= COLUMN || y >= ROW {
+            return Ok(());
         }
-        Ok(())
+        let column = match dest {
+            Destination::Frame1 => 0,
+            Destination::Frame2 => 64,
+        } + x;
+        let mask = 1 << (y % 8);
+        self.0
+            .read_modify_write(y / 8, column..column + 1, |byte| {
+                if on {
+                    byte | mask
+                } else {
+                    byte & !mask
+                }
+            })
+            .await
+    }
+
+    pub async fn status(&mut self) -> Result<Status, Error<T::Error>> {
+        self.0.status().await
     }
 
-    pub async fn is_busy(&mut self) -> Result<bool, T::Error> {
+    pub async fn is_busy(&mut self) -> Result<bool, Error<T::Error>> {
         self.0.is_busy().await
     }
 
-    pub async fn wait_while_busy(&mut self) -> Result<(), T::Error> {
+    /// Fails with [`Error::VerificationMismatch`] unless the display is in `state`, for instance to
+    /// confirm that the panel turned on after [`Self::set_state`].
+    pub async fn verify_state(&mut self, state: DisplayState) -> Result<(), Error<T::Error>> {
+        self.0.verify_display_state(state).await
+    }
+
+    /// Polls without delay nor deadline, see [`Self::wait_until_ready`] for a bounded wait.
+    pub async fn wait_while_busy(&mut self) -> Result<(), Error<T::Error>> {
         while self.is_busy().await? {}
         Ok(())
     }
+    /// See [`Sh1107::wait_until_ready`].
+    pub async fn wait_until_ready(
+        &mut self,
+        delay: &mut impl DelayNs,
+        interval_us: u32,
+        timeout_us: u32,
+    ) -> Result<(), Error<T::Error>> {
+        self.0
+            .wait_until_ready(delay, interval_us, timeout_us)
+            .await
+    }
 
     pub fn release(self) -> T {
-        self.0.release()
+        self.0.release().release()
     }
 }
 
@@ -186,52 +550,167 @@ mod embedded_graphics {
 
     use super::Destination;
     use super::Display;
+    use super::Error;
+    use super::Fixed;
     use super::SevenBitAddress;
     use embedded_graphics::pixelcolor::BinaryColor;
     use embedded_graphics::prelude::*;
+    use embedded_hal::digital::OutputPin;
+    use embedded_hal_async::delay::DelayNs;
     use itertools::Itertools;
+    use sh1107::retry::{NoRetry, RetryPolicy};
+    use sh1107::wait::{NoWait, WaitReady};
+    use sh1107::Address;
     use sh1107::AddressMode;
     use sh1107::Command;
 
-    pub struct BufferedDisplay<T, const ADDRESS: SevenBitAddress> {
-        display: Display<T, ADDRESS>,
-        bitmask: [u8; 128 * 64],
-        bitmap: [u8; 128 * 64],
+    pub struct BufferedDisplay<T, A, R = NoRetry, W = NoWait> {
+        display: Display<T, A, R, W>,
+        framebuffer: Framebuffer,
     }
-    impl<T: sh1107::WriteIter<SevenBitAddress>, const ADDRESS: SevenBitAddress> core::ops::Deref
-        for BufferedDisplay<T, ADDRESS>
+    impl<T: sh1107::WriteIter<SevenBitAddress>, A: Address, R: RetryPolicy, W: WaitReady>
+        core::ops::Deref for BufferedDisplay<T, A, R, W>
     {
-        type Target = Display<T, ADDRESS>;
-        fn deref(&self) -> &Display<T, ADDRESS> {
+        type Target = Display<T, A, R, W>;
+        fn deref(&self) -> &Display<T, A, R, W> {
             &self.display
         }
     }
-    impl<T: sh1107::WriteIter<SevenBitAddress>, const ADDRESS: SevenBitAddress> core::ops::DerefMut
-        for BufferedDisplay<T, ADDRESS>
+    impl<T: sh1107::WriteIter<SevenBitAddress>, A: Address, R: RetryPolicy, W: WaitReady>
+        core::ops::DerefMut for BufferedDisplay<T, A, R, W>
     {
-        fn deref_mut(&mut self) -> &mut Display<T, ADDRESS> {
+        fn deref_mut(&mut self) -> &mut Display<T, A, R, W> {
             &mut self.display
         }
     }
     impl<T: sh1107::WriteIter<SevenBitAddress>, const ADDRESS: SevenBitAddress>
-        BufferedDisplay<T, ADDRESS>
+        BufferedDisplay<T, Fixed<ADDRESS>>
     {
-        pub async fn new(i2c_bus: T) -> Result<Self, (T, T::Error)> {
-            // on startup the whole display is considered dirty
-            Ok(Self {
-                display: Display::new(i2c_bus).await?,
-                bitmask: [0xFF; 128 * 64],
-                bitmap: [0; 128 * 64],
-            })
+        pub async fn new(i2c_bus: T) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(Display::new(i2c_bus).await?))
+        }
+        /// See [`Display::power_up`].
+        pub async fn power_up(
+            i2c_bus: T,
+            delay: &mut impl DelayNs,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(Display::power_up(i2c_bus, delay).await?))
+        }
+        /// See [`Display::power_up_with_reset`].
+        pub async fn power_up_with_reset(
+            i2c_bus: T,
+            reset: &mut impl OutputPin,
+            delay: &mut impl DelayNs,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(
+                Display::power_up_with_reset(i2c_bus, reset, delay).await?,
+            ))
+        }
+    }
+    impl<T: sh1107::WriteIter<SevenBitAddress>> BufferedDisplay<T, SevenBitAddress> {
+        pub async fn with_address(
+            i2c_bus: T,
+            address: SevenBitAddress,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(
+                Display::with_address(i2c_bus, address).await?,
+            ))
+        }
+        pub async fn probe(i2c_bus: T) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(Display::probe(i2c_bus).await?))
+        }
+        /// See [`Display::power_up_with_address`].
+        pub async fn power_up_with_address(
+            i2c_bus: T,
+            address: SevenBitAddress,
+            delay: &mut impl DelayNs,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(
+                Display::power_up_with_address(i2c_bus, address, delay).await?,
+            ))
         }
-        pub async fn flush(&mut self) -> Result<(), <T as embedded_hal::i2c::ErrorType>::Error> {
+        /// See [`Display::power_up_with_address_and_reset`].
+        pub async fn power_up_with_address_and_reset(
+            i2c_bus: T,
+            address: SevenBitAddress,
+            reset: &mut impl OutputPin,
+            delay: &mut impl DelayNs,
+        ) -> Result<Self, (T, Error<T::Error>)> {
+            Ok(Self::from_display(
+                Display::power_up_with_address_and_reset(i2c_bus, address, reset, delay).await?,
+            ))
+        }
+    }
+    impl<T, A, R, W> BufferedDisplay<T, A, R, W>
+    where
+        T: sh1107::WriteIter<SevenBitAddress>,
+        A: Address,
+        R: RetryPolicy,
+        W: WaitReady,
+    {
+        fn from_display(display: Display<T, A, R, W>) -> Self {
+            Self {
+                display,
+                framebuffer: Framebuffer::new(),
+            }
+        }
+        /// See [`Display::with_retry`].
+        pub fn with_retry<R2: RetryPolicy>(self, retry: R2) -> BufferedDisplay<T, A, R2, W> {
+            BufferedDisplay {
+                display: self.display.with_retry(retry),
+                framebuffer: self.framebuffer,
+            }
+        }
+        /// See [`Display::with_ready_wait`], the wait also applies to [`Self::flush`].
+        pub fn with_ready_wait<W2: WaitReady>(self, wait: W2) -> BufferedDisplay<T, A, R, W2> {
+            BufferedDisplay {
+                display: self.display.with_ready_wait(wait),
+                framebuffer: self.framebuffer,
+            }
+        }
+        pub async fn flush(
+            &mut self,
+        ) -> Result<(), Error<<T as embedded_hal::i2c::ErrorType>::Error>> {
             self.flush_to(Destination::Frame1).await
         }
         pub async fn flush_to(
             &mut self,
             destination: Destination,
-        ) -> Result<(), <T as embedded_hal::i2c::ErrorType>::Error> {
-            self.display
+        ) -> Result<(), Error<<T as embedded_hal::i2c::ErrorType>::Error>> {
+            self.framebuffer
+                .flush_to(&mut self.display, destination)
+                .await
+        }
+    }
+
+    /// Local copy of the frame, along with a mask of the pixels changed since the last flush.
+    ///
+    /// Shared by the async and blocking [`BufferedDisplay`]s.
+    pub(crate) struct Framebuffer {
+        bitmask: [u8; 128 * 64],
+        bitmap: [u8; 128 * 64],
+    }
+    impl Framebuffer {
+        pub(crate) fn new() -> Self {
+            // on startup the whole display is considered dirty
+            Self {
+                bitmask: [0xFF; 128 * 64],
+                bitmap: [0; 128 * 64],
+            }
+        }
+        pub(crate) async fn flush_to<T, A, R, W>(
+            &mut self,
+            display: &mut Display<T, A, R, W>,
+            destination: Destination,
+        ) -> Result<(), Error<T::Error>>
+        where
+            T: sh1107::WriteIter<SevenBitAddress>,
+            A: Address,
+            R: RetryPolicy,
+            W: WaitReady,
+        {
+            display.1.wait_ready(&mut display.0).await?;
+            display
                 .0
                 .run([Command::SetAddressMode(AddressMode::Page)])
                 .await?;
@@ -287,7 +766,7 @@ mod embedded_graphics {
             });
 
             for ((page, col), range) in pages {
-                self.display
+                display
                     .0
                     .run_then_write_to_ram(
                         [
@@ -307,22 +786,9 @@ mod embedded_graphics {
 
             Ok(())
         }
-    }
-
-    impl<T, const ADDRESS: SevenBitAddress> OriginDimensions for BufferedDisplay<T, ADDRESS> {
-        fn size(&self) -> Size {
-            Size::new(64, 128)
-        }
-    }
-
-    impl<T, const ADDRESS: SevenBitAddress> DrawTarget for BufferedDisplay<T, ADDRESS> {
-        type Color = BinaryColor;
-
-        type Error = core::convert::Infallible;
-
-        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        pub(crate) fn draw_iter<I>(&mut self, pixels: I)
         where
-            I: IntoIterator<Item = Pixel<Self::Color>>,
+            I: IntoIterator<Item = Pixel<BinaryColor>>,
         {
             for Pixel(coord, color) in pixels
                 .into_iter()
@@ -340,7 +806,123 @@ mod embedded_graphics {
                 self.bitmask[idx as usize] |= mask;
                 self.bitmap[idx as usize] = (self.bitmap[idx as usize] & !mask) | pixel;
             }
+        }
+    }
+
+    impl<T, A, R, W> OriginDimensions for BufferedDisplay<T, A, R, W> {
+        fn size(&self) -> Size {
+            Size::new(64, 128)
+        }
+    }
+
+    impl<T, A, R, W> DrawTarget for BufferedDisplay<T, A, R, W> {
+        type Color = BinaryColor;
+
+        type Error = core::convert::Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            self.framebuffer.draw_iter(pixels);
             Ok(())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIRECTIONS: [Direction; 2] = [Direction::Normal, Direction::Inverted];
+
+    fn bands() -> impl Iterator<Item = Range<u8>> {
+        (0..COLUMN).flat_map(|start| (start + 1..=COLUMN).map(move |end| start..end))
+    }
+
+    /// RAM line shown on `row` of the panel, the scan starting from the first scanned row.
+    fn shown_line(direction: Direction, band: &Range<u8>, start_line: u8, row: u8) -> u8 {
+        let scanned = match direction {
+            Direction::Normal => row - band.start,
+            Direction::Inverted => band.end - 1 - row,
+        };
+        (start_line + scanned) % 128
+    }
+
+    #[test]
+    fn display_offset_follows_the_wiring() {
+        assert_eq!(display_offset(Direction::Normal, &(0..COLUMN)), 96);
+        assert_eq!(display_offset(Direction::Inverted, &(0..COLUMN)), 32);
+        assert_eq!(display_offset(Direction::Normal, &(8..16)), 104);
+        assert_eq!(display_offset(Direction::Inverted, &(8..16)), 80);
+        assert_eq!(display_offset(Direction::Normal, &(40..64)), 8);
+        assert_eq!(display_offset(Direction::Inverted, &(0..1)), 95);
+    }
+
+    #[test]
+    fn band_round_trips() {
+        for direction in DIRECTIONS {
+            for b in bands() {
+                let offset = display_offset(direction, &b);
+                let len = b.end - b.start;
+                assert_eq!(
+                    band(direction, len, offset),
+                    Some(b.clone()),
+                    "{direction:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn band_outside_the_panel_is_none() {
+        // rows 64.. are not wired
+        assert_eq!(band(Direction::Normal, 8, 96 + 60), None);
+        assert_eq!(band(Direction::Normal, 64, 97), None);
+        assert_eq!(band(Direction::Inverted, 64, 31), None);
+        assert_eq!(band(Direction::Inverted, 8, 30), None);
+    }
+
+    #[test]
+    fn first_scanned_row_is_the_top_or_the_bottom() {
+        assert_eq!(first_scanned_row(Direction::Normal, &(8..16)), 8);
+        assert_eq!(first_scanned_row(Direction::Inverted, &(8..16)), 48);
+        for direction in DIRECTIONS {
+            assert_eq!(first_scanned_row(direction, &(0..COLUMN)), 0);
+        }
+    }
+
+    #[test]
+    fn start_line_round_trips() {
+        for direction in DIRECTIONS {
+            for b in bands() {
+                for base in 0..128 {
+                    let start = start_line(base, direction, &b);
+                    assert_eq!(base_line(start, direction, &b), base);
+                }
+            }
+            assert_eq!(start_line(5, direction, &(0..COLUMN)), 5);
+        }
+        assert_eq!(start_line(5, Direction::Normal, &(8..16)), 13);
+        assert_eq!(start_line(5, Direction::Inverted, &(8..16)), 53);
+        assert_eq!(start_line(100, Direction::Inverted, &(0..8)), 28);
+    }
+
+    #[test]
+    fn band_keeps_the_content_of_its_rows() {
+        for direction in DIRECTIONS {
+            for base in [0, 5, 127] {
+                for b in bands() {
+                    let start = start_line(base, direction, &b);
+                    for row in b.clone() {
+                        assert_eq!(
+                            shown_line(direction, &b, start, row),
+                            shown_line(direction, &(0..COLUMN), base, row),
+                            "{direction:?} {b:?} {row}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
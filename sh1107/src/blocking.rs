@@ -0,0 +1,390 @@
+//! Blocking API.
+//!
+//! The driver is written once, against the async traits. [`Blocking`] adapts blocking bus
+//! implementations to these traits: the futures it returns complete on their first poll, and
+//! [`block_on`] drives them to completion without an executor.
+//!
+//! A blocking I²C bus is used as `Sh1107::new(I2cInterface::new(Blocking::new(i2c)))`.
+
+use core::future::Future;
+use core::num::NonZeroUsize;
+use core::ops::Range;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+pub use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{AddressMode as I2cAddressMode, SevenBitAddress};
+pub use i2c_write_iter::WriteIter;
+
+use crate::observer::{NoObserver, Observer};
+use crate::retry::{NoRetry, RetryPolicy};
+use crate::{
+    AddressMode, Command, DisplayState, Error, I2cInterface, Interface, ReadInterface, Registers,
+    Status,
+};
+
+/// Runs `future` to completion, polling it in a loop.
+///
+/// Futures built on top of [`Blocking`] buses complete on their first poll. Any other future is
+/// busy-polled until it completes.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Implements the async bus traits on top of a blocking bus implementation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Blocking<T>(T);
+
+impl<T> Blocking<T> {
+    pub fn new(bus: T) -> Self {
+        Self(bus)
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.0
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: embedded_hal::i2c::ErrorType> embedded_hal::i2c::ErrorType for Blocking<T> {
+    type Error = T::Error;
+}
+
+impl<T, A> embedded_hal_async::i2c::I2c<A> for Blocking<T>
+where
+    T: embedded_hal::i2c::I2c<A>,
+    A: I2cAddressMode,
+{
+    async fn read(&mut self, address: A, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(address, read)
+    }
+
+    async fn write(&mut self, address: A, write: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, write)
+    }
+
+    async fn write_read(
+        &mut self,
+        address: A,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.0.write_read(address, write, read)
+    }
+
+    async fn transaction(
+        &mut self,
+        address: A,
+        operations: &mut [embedded_hal::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.0.transaction(address, operations)
+    }
+}
+
+impl<T, A> i2c_write_iter::non_blocking::WriteIter<A> for Blocking<T>
+where
+    T: WriteIter<A>,
+    A: I2cAddressMode,
+{
+    async fn write_iter<'a, U>(&'a mut self, address: A, bytes: U) -> Result<(), Self::Error>
+    where
+        U: IntoIterator<Item = u8> + 'a,
+    {
+        self.0.write_iter(address, bytes)
+    }
+}
+
+impl<T: embedded_hal::spi::ErrorType> embedded_hal::spi::ErrorType for Blocking<T> {
+    type Error = T::Error;
+}
+
+impl<T, Word> embedded_hal_async::spi::SpiDevice<Word> for Blocking<T>
+where
+    T: embedded_hal::spi::SpiDevice<Word>,
+    Word: Copy + 'static,
+{
+    async fn transaction(
+        &mut self,
+        operations: &mut [embedded_hal::spi::Operation<'_, Word>],
+    ) -> Result<(), Self::Error> {
+        self.0.transaction(operations)
+    }
+
+    async fn write(&mut self, buf: &[Word]) -> Result<(), Self::Error> {
+        self.0.write(buf)
+    }
+}
+
+impl<T: DelayNs> embedded_hal_async::delay::DelayNs for Blocking<T> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_ns(ns)
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us)
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms)
+    }
+}
+
+#[cfg(feature = "display-interface")]
+mod display_interface {
+    use display_interface::{
+        AsyncWriteOnlyDataCommand, DataFormat, DisplayError, WriteOnlyDataCommand,
+    };
+    use embedded_hal::i2c::SevenBitAddress;
+
+    use super::{block_on, Blocking, WriteIter};
+    use crate::{Address, BufferedI2cInterface, I2cInterface};
+
+    impl<DI: WriteOnlyDataCommand> AsyncWriteOnlyDataCommand for Blocking<DI> {
+        async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+            self.0.send_commands(cmd)
+        }
+
+        async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+            self.0.send_data(buf)
+        }
+    }
+
+    impl<T, A> WriteOnlyDataCommand for I2cInterface<Blocking<T>, A>
+    where
+        T: WriteIter<SevenBitAddress>,
+        A: Address,
+    {
+        fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+            block_on(AsyncWriteOnlyDataCommand::send_commands(self, cmd))
+        }
+
+        fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+            block_on(AsyncWriteOnlyDataCommand::send_data(self, buf))
+        }
+    }
+
+    impl<T, A, const N: usize> WriteOnlyDataCommand for BufferedI2cInterface<Blocking<T>, A, N>
+    where
+        T: embedded_hal::i2c::I2c<SevenBitAddress>,
+        A: Address,
+    {
+        fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+            block_on(AsyncWriteOnlyDataCommand::send_commands(self, cmd))
+        }
+
+        fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+            block_on(AsyncWriteOnlyDataCommand::send_data(self, buf))
+        }
+    }
+}
+
+/// Blocking counterpart of [`crate::Sh1107`].
+pub struct Sh1107<DI, R = NoRetry, O = NoObserver>(crate::Sh1107<DI, R, O>);
+
+impl<DI> Sh1107<DI> {
+    pub fn new(interface: DI) -> Self {
+        Self(crate::Sh1107::new(interface))
+    }
+}
+
+impl<DI, R, O> Sh1107<DI, R, O> {
+    /// See [`crate::Sh1107::with_retry`], blocking delays are used through [`Blocking`].
+    pub fn with_retry<R2: RetryPolicy>(self, retry: R2) -> Sh1107<DI, R2, O> {
+        Sh1107(self.0.with_retry(retry))
+    }
+
+    pub fn retry_policy(&self) -> &R {
+        self.0.retry_policy()
+    }
+
+    pub fn retry_policy_mut(&mut self) -> &mut R {
+        self.0.retry_policy_mut()
+    }
+
+    /// See [`crate::Sh1107::with_observer`].
+    pub fn with_observer<O2>(self, observer: O2) -> Sh1107<DI, R, O2> {
+        Sh1107(self.0.with_observer(observer))
+    }
+
+    pub fn observer(&self) -> &O {
+        self.0.observer()
+    }
+
+    pub fn observer_mut(&mut self) -> &mut O {
+        self.0.observer_mut()
+    }
+
+    /// See [`crate::Sh1107::registers`].
+    pub fn registers(&self) -> &Registers {
+        self.0.registers()
+    }
+
+    /// See [`crate::Sh1107::invalidate_registers`].
+    pub fn invalidate_registers(&mut self) {
+        self.0.invalidate_registers()
+    }
+
+    pub fn is_asleep(&self) -> bool {
+        self.0.is_asleep()
+    }
+
+    pub fn interface(&self) -> &DI {
+        self.0.interface()
+    }
+
+    pub fn interface_mut(&mut self) -> &mut DI {
+        self.0.interface_mut()
+    }
+
+    /// See [`crate::Sh1107::set_max_transfer`].
+    pub fn set_max_transfer(&mut self, max_transfer: Option<NonZeroUsize>) {
+        self.0.set_max_transfer(max_transfer)
+    }
+
+    pub fn max_transfer(&self) -> Option<NonZeroUsize> {
+        self.0.max_transfer()
+    }
+
+    pub fn release(self) -> DI {
+        self.0.release()
+    }
+}
+
+impl<T> Sh1107<I2cInterface<Blocking<T>, SevenBitAddress>>
+where
+    T: WriteIter<SevenBitAddress>,
+{
+    /// See [`crate::Sh1107::probe`].
+    pub fn probe(i2c: T) -> Result<Self, (T, Error<T::Error>)> {
+        block_on(crate::Sh1107::probe(Blocking::new(i2c)))
+            .map(Self)
+            .map_err(|(i2c, e)| (i2c.into_inner(), e))
+    }
+}
+
+impl<DI, R, O> Sh1107<DI, R, O>
+where
+    DI: Interface,
+    R: RetryPolicy,
+    O: Observer<DI::Error>,
+{
+    /// See [`crate::Sh1107::reset`].
+    pub fn reset(
+        &mut self,
+        reset: &mut impl embedded_hal::digital::OutputPin,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<DI::Error>> {
+        let mut delay = Blocking::new(delay);
+        block_on(self.0.reset(reset, &mut delay))
+    }
+
+    pub fn run<C>(&mut self, commands: C) -> Result<(), Error<DI::Error>>
+    where
+        C: IntoIterator<Item = Command>,
+        C::IntoIter: Clone,
+    {
+        block_on(self.0.run(commands))
+    }
+
+    pub fn run_encoded(&mut self, commands: &[u8]) -> Result<(), Error<DI::Error>> {
+        block_on(self.0.run_encoded(commands))
+    }
+
+    /// See [`crate::Sh1107::sleep`].
+    pub fn sleep(&mut self) -> Result<(), Error<DI::Error>> {
+        block_on(self.0.sleep())
+    }
+
+    /// See [`crate::Sh1107::wake`].
+    pub fn wake(&mut self) -> Result<(), Error<DI::Error>> {
+        block_on(self.0.wake())
+    }
+
+    pub fn write_to_ram(
+        &mut self,
+        data: impl IntoIterator<Item = u8>,
+    ) -> Result<(), Error<DI::Error>> {
+        block_on(self.0.write_to_ram(data))
+    }
+
+    pub fn run_then_write_to_ram<C>(
+        &mut self,
+        commands: C,
+        data: impl IntoIterator<Item = u8>,
+    ) -> Result<(), Error<DI::Error>>
+    where
+        C: IntoIterator<Item = Command>,
+        C::IntoIter: Clone,
+    {
+        block_on(self.0.run_then_write_to_ram(commands, data))
+    }
+}
+
+impl<DI, R, O> Sh1107<DI, R, O>
+where
+    DI: ReadInterface,
+    R: RetryPolicy,
+    O: Observer<DI::Error>,
+{
+    pub fn read_from_ram(&mut self, buf: &mut [u8]) -> Result<(), Error<DI::Error>> {
+        block_on(self.0.read_from_ram(buf))
+    }
+
+    /// See [`crate::Sh1107::read_region`].
+    pub fn read_region(
+        &mut self,
+        mode: AddressMode,
+        pages: Range<u8>,
+        columns: Range<u8>,
+        buf: &mut [u8],
+    ) -> Result<(), Error<DI::Error>> {
+        block_on(self.0.read_region(mode, pages, columns, buf))
+    }
+
+    /// See [`crate::Sh1107::read_modify_write`].
+    pub fn read_modify_write(
+        &mut self,
+        page: u8,
+        columns: Range<u8>,
+        modify: impl FnMut(u8) -> u8,
+    ) -> Result<(), Error<DI::Error>> {
+        block_on(self.0.read_modify_write(page, columns, modify))
+    }
+
+    pub fn status(&mut self) -> Result<Status, Error<DI::Error>> {
+        block_on(self.0.status())
+    }
+
+    pub fn is_busy(&mut self) -> Result<bool, Error<DI::Error>> {
+        block_on(self.0.is_busy())
+    }
+
+    /// See [`crate::Sh1107::wait_until_ready`].
+    pub fn wait_until_ready(
+        &mut self,
+        delay: &mut impl DelayNs,
+        interval_us: u32,
+        timeout_us: u32,
+    ) -> Result<(), Error<DI::Error>> {
+        let mut delay = Blocking::new(delay);
+        block_on(self.0.wait_until_ready(&mut delay, interval_us, timeout_us))
+    }
+
+    /// See [`crate::Sh1107::verify_display_state`].
+    pub fn verify_display_state(&mut self, expected: DisplayState) -> Result<(), Error<DI::Error>> {
+        block_on(self.0.verify_display_state(expected))
+    }
+}
@@ -0,0 +1,415 @@
+//! Decoder for the control-byte framed stream written to the controller.
+//!
+//! Each I²C write transaction starts with a control byte where bit 7 (Co) tells whether another
+//! control byte follows the next byte, and bit 6 (D/C#) tells whether that byte (or the rest of
+//! the transaction when Co is clear) is data or command.
+
+use crate::{
+    AddressMode, ChargePeriod, Command, DcDcSettings, Direction, DisplayMode, DisplayState,
+    InvalidArgument, OscFrequency, VcomhLevel,
+};
+
+const CONTINUATION: u8 = 0x80;
+const DATA: u8 = 0x40;
+
+/// Item decoded from a transaction.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Frame<'a> {
+    Command(Command),
+    /// Lower nibble of the column address, sent without the higher one.
+    ///
+    /// The higher nibble of the column address is left unchanged.
+    LowerColumnAddress(u8),
+    /// Higher nibble of the column address, sent without the lower one.
+    ///
+    /// The lower nibble of the column address is left unchanged.
+    HigherColumnAddress(u8),
+    /// A run of bytes written to the display RAM.
+    Data(&'a [u8]),
+}
+
+/// Reason why a transaction could not be decoded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodeError {
+    /// The transaction ended where a control byte announced one more byte.
+    UnexpectedEnd,
+    /// The opcode does not match any [`Command`].
+    UnknownOpcode(u8),
+    /// The opcode requires a parameter byte that is not part of the command stream.
+    MissingParameter(u8),
+    /// The parameter byte does not encode a valid value for this opcode.
+    InvalidParameter { opcode: u8, parameter: u8 },
+    /// The decoded command does not pass [`Command::validate`].
+    InvalidArgument(InvalidArgument),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Stream {
+    Command { single: bool },
+    Data { single: bool },
+}
+
+impl Stream {
+    fn from_control(control: u8) -> Self {
+        let single = (control & CONTINUATION) != 0;
+        if (control & DATA) != 0 {
+            Self::Data { single }
+        } else {
+            Self::Command { single }
+        }
+    }
+}
+
+/// Iterates over the commands and data runs of a single write transaction.
+///
+/// Decoding stops after the first error.
+#[derive(Clone, Debug)]
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    /// Framing of the next byte, `None` when a control byte is expected.
+    stream: Option<Stream>,
+}
+
+impl<'a> Decoder<'a> {
+    /// `transaction` is the raw content of the write transaction, starting with a control byte.
+    pub fn new(transaction: &'a [u8]) -> Self {
+        Self {
+            bytes: transaction,
+            stream: None,
+        }
+    }
+
+    /// `commands` is a bare command stream, as sent with D/C# low on the serial and parallel
+    /// interfaces.
+    pub fn commands(commands: &'a [u8]) -> Self {
+        Self {
+            bytes: commands,
+            stream: Some(Stream::Command { single: false }),
+        }
+    }
+
+    fn fail(&mut self, error: DecodeError) -> Option<Result<Frame<'a>, DecodeError>> {
+        self.bytes = &[];
+        self.stream = None;
+        Some(Err(error))
+    }
+
+    /// Returns the next byte of the command stream, skipping over the control bytes of
+    /// single-byte frames.
+    fn next_command_byte(&mut self) -> Option<u8> {
+        loop {
+            match self.stream {
+                None => {
+                    let (&control, rest) = self.bytes.split_first()?;
+                    if (control & DATA) != 0 {
+                        return None;
+                    }
+                    self.bytes = rest;
+                    self.stream = Some(Stream::from_control(control));
+                }
+                Some(Stream::Command { single }) => {
+                    let (&byte, rest) = self.bytes.split_first()?;
+                    self.bytes = rest;
+                    if single {
+                        self.stream = None;
+                    }
+                    return Some(byte);
+                }
+                Some(Stream::Data { .. }) => return None,
+            }
+        }
+    }
+
+    /// Pairs a column address nibble with the next command byte if it holds the other nibble.
+    fn decode_column(&mut self, opcode: u8) -> Frame<'a> {
+        let mut ahead = self.clone();
+        match (opcode, ahead.next_command_byte()) {
+            (lower @ 0x00..=0x0F, Some(higher @ 0x10..=0x17))
+            | (higher @ 0x10..=0x17, Some(lower @ 0x00..=0x0F)) => {
+                *self = ahead;
+                Frame::Command(Command::SetColumnAddress(((higher & 0x7) << 4) | lower))
+            }
+            (0x00..=0x0F, _) => Frame::LowerColumnAddress(opcode),
+            _ => Frame::HigherColumnAddress(opcode & 0x7),
+        }
+    }
+
+    fn decode_command(&mut self, opcode: u8) -> Result<Command, DecodeError> {
+        let mut parameter = || {
+            self.next_command_byte()
+                .ok_or(DecodeError::MissingParameter(opcode))
+        };
+        let invalid = |parameter| DecodeError::InvalidParameter { opcode, parameter };
+
+        let command = match opcode {
+            0x20 => Command::SetAddressMode(AddressMode::Page),
+            0x21 => Command::SetAddressMode(AddressMode::Column),
+            0x81 => Command::SetContrastControl(parameter()?),
+            0xA0 | 0xA1 => Command::SetSegmentReMap(opcode == 0xA1),
+            0xA4 | 0xA5 => Command::ForceEntireDisplay(opcode == 0xA5),
+            0xA6 => Command::SetDisplayMode(DisplayMode::BlackOnWhite),
+            0xA7 => Command::SetDisplayMode(DisplayMode::WhiteOnBlack),
+            0xA8 => match parameter()? {
+                ratio @ 0x00..=0x7F => Command::SetMultiplexRatio(ratio + 1),
+                other => return Err(invalid(other)),
+            },
+            0xAD => match parameter()? {
+                cfg @ 0x80..=0x8F => Command::SetDCDCSettings(DcDcSettings::from_bits(cfg)),
+                other => return Err(invalid(other)),
+            },
+            0xAE => Command::DisplayOnOff(DisplayState::Off),
+            0xAF => Command::DisplayOnOff(DisplayState::On),
+            0xB0..=0xBF => Command::SetPageAddress(opcode & 0x0F),
+            0xC0..=0xCF => Command::SetCOMScanDirection(if (opcode & 0x08) == 0 {
+                Direction::Normal
+            } else {
+                Direction::Inverted
+            }),
+            0xD3 => match parameter()? {
+                offset @ 0x00..=0x7F => Command::SetDisplayOffset(offset),
+                other => return Err(invalid(other)),
+            },
+            0xD5 => {
+                let arg = parameter()?;
+                Command::SetClkDividerOscFrequency {
+                    divider: (arg & 0x0F) + 1,
+                    osc_freq: OscFrequency::from_bits(arg >> 4),
+                }
+            }
+            0xD9 => {
+                let arg = parameter()?;
+                Command::SetChargePeriods {
+                    precharge: ChargePeriod::new(arg & 0x0F),
+                    discharge: ChargePeriod::new(arg >> 4).ok_or(invalid(arg))?,
+                }
+            }
+            0xDB => Command::SetVCOMHDeselectLevel(VcomhLevel::from_bits(parameter()?)),
+            0xDC => Command::SetStartLine(parameter()?),
+            0xE0 => Command::StartReadModifyWrite,
+            0xE3 => Command::Nop,
+            0xEE => Command::EndReadModifyWrite,
+            _ => return Err(DecodeError::UnknownOpcode(opcode)),
+        };
+        command.validate().map_err(DecodeError::InvalidArgument)?;
+        Ok(command)
+    }
+}
+
+impl<'a> Iterator for Decoder<'a> {
+    type Item = Result<Frame<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stream {
+                None => {
+                    let (&control, rest) = self.bytes.split_first()?;
+                    self.bytes = rest;
+                    self.stream = Some(Stream::from_control(control));
+                }
+                Some(Stream::Data { single }) => {
+                    if self.bytes.is_empty() {
+                        return if single {
+                            self.fail(DecodeError::UnexpectedEnd)
+                        } else {
+                            None
+                        };
+                    }
+                    let len = if single { 1 } else { self.bytes.len() };
+                    let (data, rest) = self.bytes.split_at(len);
+                    self.bytes = rest;
+                    if single {
+                        self.stream = None;
+                    }
+                    return Some(Ok(Frame::Data(data)));
+                }
+                Some(Stream::Command { single }) => {
+                    let Some(opcode) = self.next_command_byte() else {
+                        return if single {
+                            self.fail(DecodeError::UnexpectedEnd)
+                        } else {
+                            None
+                        };
+                    };
+                    if let 0x00..=0x17 = opcode {
+                        return Some(Ok(self.decode_column(opcode)));
+                    }
+                    return match self.decode_command(opcode) {
+                        Ok(command) => Some(Ok(Frame::Command(command))),
+                        Err(e) => self.fail(e),
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(transaction: &[u8]) -> ([Option<Result<Frame<'_>, DecodeError>>; 4], usize) {
+        let mut decoder = Decoder::new(transaction);
+        let frames = core::array::from_fn(|_| decoder.next());
+        (frames, decoder.count())
+    }
+
+    #[test]
+    fn every_command_round_trips() {
+        for command in crate::every_command() {
+            let encoded = command.try_encode().unwrap();
+            let mut transaction = [0u8; 3];
+            transaction[1..][..encoded.as_bytes().len()].copy_from_slice(encoded.as_bytes());
+            let transaction = &transaction[..1 + encoded.as_bytes().len()];
+
+            let mut decoder = Decoder::new(transaction);
+            assert_eq!(
+                decoder.next(),
+                Some(Ok(Frame::Command(command))),
+                "{command:?}"
+            );
+            assert_eq!(decoder.next(), None, "{command:?}");
+
+            let mut decoder = Decoder::commands(encoded.as_bytes());
+            assert_eq!(
+                decoder.next(),
+                Some(Ok(Frame::Command(command))),
+                "{command:?}"
+            );
+            assert_eq!(decoder.next(), None, "{command:?}");
+        }
+    }
+
+    #[test]
+    fn single_command_frames_then_data_run() {
+        // the contrast parameter is framed on its own, as sent by `write_commands_then_data`
+        let transaction = [0x80, 0xAF, 0x80, 0x81, 0x80, 0x7F, 0x40, 1, 2, 3];
+        let (frames, rest) = decode(&transaction);
+        assert_eq!(
+            frames,
+            [
+                Some(Ok(Frame::Command(Command::DisplayOnOff(DisplayState::On)))),
+                Some(Ok(Frame::Command(Command::SetContrastControl(0x7F)))),
+                Some(Ok(Frame::Data(&[1, 2, 3]))),
+                None,
+            ]
+        );
+        assert_eq!(rest, 0);
+    }
+
+    #[test]
+    fn single_data_byte_then_command() {
+        let transaction = [0xC0, 0x55, 0x80, 0xE3, 0x40, 0xAA];
+        let (frames, _) = decode(&transaction);
+        assert_eq!(
+            frames,
+            [
+                Some(Ok(Frame::Data(&[0x55]))),
+                Some(Ok(Frame::Command(Command::Nop))),
+                Some(Ok(Frame::Data(&[0xAA]))),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn command_stream_runs_to_the_end() {
+        // with Co clear, 0x40 is an opcode-looking byte of the stream, not a control byte
+        let transaction = [0x00, 0xAE, 0x40, 0x02];
+        let (frames, _) = decode(&transaction);
+        assert_eq!(
+            frames,
+            [
+                Some(Ok(Frame::Command(Command::DisplayOnOff(DisplayState::Off)))),
+                Some(Err(DecodeError::UnknownOpcode(0x40))),
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_stop_decoding() {
+        let cases: [(&[u8], DecodeError); 6] = [
+            (&[0x00, 0x81], DecodeError::MissingParameter(0x81)),
+            (
+                &[0x80, 0x81, 0x40, 0x7F],
+                DecodeError::MissingParameter(0x81),
+            ),
+            (&[0x80], DecodeError::UnexpectedEnd),
+            (&[0xC0], DecodeError::UnexpectedEnd),
+            (
+                &[0x00, 0xA8, 0x80, 0xAF],
+                DecodeError::InvalidParameter {
+                    opcode: 0xA8,
+                    parameter: 0x80,
+                },
+            ),
+            (
+                &[0x00, 0xDC, 0x80],
+                DecodeError::InvalidArgument(InvalidArgument(Command::SetStartLine(0x80))),
+            ),
+        ];
+        for (transaction, error) in cases {
+            let (frames, rest) = decode(transaction);
+            assert_eq!(frames[0], Some(Err(error)), "{transaction:02X?}");
+            assert_eq!(frames[1], None, "{transaction:02X?}");
+            assert_eq!(rest, 0, "{transaction:02X?}");
+        }
+    }
+
+    #[test]
+    fn lone_column_nibbles_are_partial_addresses() {
+        use Frame::*;
+
+        let cases: [(&[u8], [Option<Frame>; 3]); 6] = [
+            (
+                &[0x00, 0x05, 0xAF],
+                [
+                    Some(LowerColumnAddress(0x5)),
+                    Some(Command(crate::Command::DisplayOnOff(DisplayState::On))),
+                    None,
+                ],
+            ),
+            (&[0x00, 0x12], [Some(HigherColumnAddress(0x2)), None, None]),
+            (
+                &[0x00, 0x05, 0x03],
+                [
+                    Some(LowerColumnAddress(0x5)),
+                    Some(LowerColumnAddress(0x3)),
+                    None,
+                ],
+            ),
+            (
+                &[0x80, 0x05, 0x40, 0x01],
+                [Some(LowerColumnAddress(0x5)), Some(Data(&[0x01])), None],
+            ),
+            // the pair is still recognised across single-byte frames and in either order
+            (
+                &[0x80, 0x05, 0x80, 0x12],
+                [
+                    Some(Command(crate::Command::SetColumnAddress(0x25))),
+                    None,
+                    None,
+                ],
+            ),
+            (
+                &[0x00, 0x12, 0x05],
+                [
+                    Some(Command(crate::Command::SetColumnAddress(0x25))),
+                    None,
+                    None,
+                ],
+            ),
+        ];
+        for (transaction, expected) in cases {
+            let (frames, _) = decode(transaction);
+            assert_eq!(
+                frames[..3],
+                expected.map(|frame| frame.map(Ok)),
+                "{transaction:02X?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Integration with the [`display_interface`] traits.
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+use i2c_write_iter::non_blocking::WriteIter;
+
+use super::{Address, BufferedI2cInterface, I2cInterface, Interface};
+
+/// Interface on top of any [`AsyncWriteOnlyDataCommand`] implementation.
+///
+/// Only [`DataFormat::U8Iter`] is used, and the commands and data are always sent separately.
+pub struct DataCommandInterface<DI>(DI);
+
+impl<DI> DataCommandInterface<DI> {
+    pub fn new(interface: DI) -> Self {
+        Self(interface)
+    }
+
+    pub fn release(self) -> DI {
+        self.0
+    }
+}
+
+impl<DI: AsyncWriteOnlyDataCommand> Interface for DataCommandInterface<DI> {
+    type Error = DisplayError;
+
+    async fn write_commands(
+        &mut self,
+        mut commands: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        self.0
+            .send_commands(DataFormat::U8Iter(&mut commands))
+            .await
+    }
+
+    async fn write_data(&mut self, mut data: impl Iterator<Item = u8>) -> Result<(), Self::Error> {
+        self.0.send_data(DataFormat::U8Iter(&mut data)).await
+    }
+}
+
+/// Sends `format` as commands (`0x00` control byte) or data (`0x40` control byte).
+///
+/// Only [`DataFormat::U8`] and [`DataFormat::U8Iter`] are supported.
+async fn send<DI: Interface>(
+    interface: &mut DI,
+    format: DataFormat<'_>,
+    data: bool,
+) -> Result<(), DisplayError> {
+    let res = match (format, data) {
+        (DataFormat::U8(bytes), false) => interface.write_commands(bytes.iter().copied()).await,
+        (DataFormat::U8(bytes), true) => interface.write_data(bytes.iter().copied()).await,
+        (DataFormat::U8Iter(bytes), false) => interface.write_commands(bytes).await,
+        (DataFormat::U8Iter(bytes), true) => interface.write_data(bytes).await,
+        _ => return Err(DisplayError::DataFormatNotImplemented),
+    };
+    res.map_err(|_| DisplayError::BusWriteError)
+}
+
+impl<T, A> AsyncWriteOnlyDataCommand for I2cInterface<T, A>
+where
+    T: WriteIter<SevenBitAddress>,
+    A: Address,
+{
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        send(self, cmd, false).await
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        send(self, buf, true).await
+    }
+}
+
+impl<T, A, const N: usize> AsyncWriteOnlyDataCommand for BufferedI2cInterface<T, A, N>
+where
+    T: I2c<SevenBitAddress>,
+    A: Address,
+{
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        send(self, cmd, false).await
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        send(self, buf, true).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use embedded_hal_async::i2c::{ErrorKind, ErrorType, Operation};
+
+    use super::*;
+    use crate::blocking::block_on;
+    use crate::Fixed;
+
+    /// Records the commands and data sent, the data flagged with `true`.
+    #[derive(Default)]
+    struct Recorder(Vec<(bool, Vec<u8>)>);
+
+    impl AsyncWriteOnlyDataCommand for Recorder {
+        async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+            match cmd {
+                DataFormat::U8Iter(bytes) => self.0.push((false, bytes.collect())),
+                _ => unreachable!(),
+            }
+            Ok(())
+        }
+
+        async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+            match buf {
+                DataFormat::U8Iter(bytes) => self.0.push((true, bytes.collect())),
+                _ => unreachable!(),
+            }
+            Ok(())
+        }
+    }
+
+    /// I²C bus recording the writes, or failing with `error`.
+    #[derive(Default)]
+    struct Bus {
+        writes: Vec<Vec<u8>>,
+        error: Option<ErrorKind>,
+    }
+
+    impl ErrorType for Bus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for Bus {
+        async fn transaction(
+            &mut self,
+            _address: SevenBitAddress,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            if let Some(error) = self.error {
+                return Err(error);
+            }
+            for operation in operations {
+                if let Operation::Write(bytes) = operation {
+                    self.writes.push(bytes.to_vec());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn commands_and_data_are_sent_separately() {
+        let mut interface = DataCommandInterface::new(Recorder::default());
+        block_on(interface.write_commands([0xAE, 0xAF].into_iter())).unwrap();
+        block_on(interface.write_commands_then_data([0xB0].into_iter(), [1, 2].into_iter()))
+            .unwrap();
+        assert_eq!(
+            interface.release().0,
+            [
+                (false, std::vec![0xAE, 0xAF]),
+                (false, std::vec![0xB0]),
+                (true, std::vec![1, 2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn i2c_interfaces_frame_commands_and_data() {
+        let mut interface = BufferedI2cInterface::<_, Fixed<0x3C>>::new(Bus::default());
+        block_on(interface.send_commands(DataFormat::U8(&[0xAE, 0x81, 0x7F]))).unwrap();
+        block_on(interface.send_data(DataFormat::U8Iter(&mut [1, 2].into_iter()))).unwrap();
+        assert_eq!(
+            interface.release().writes,
+            [std::vec![0x00, 0xAE, 0x81, 0x7F], std::vec![0x40, 1, 2]]
+        );
+    }
+
+    #[test]
+    fn errors_are_mapped_to_display_errors() {
+        let bus = Bus {
+            error: Some(ErrorKind::ArbitrationLoss),
+            ..Bus::default()
+        };
+        let mut interface = BufferedI2cInterface::<_, Fixed<0x3C>>::new(bus);
+        assert!(matches!(
+            block_on(interface.send_commands(DataFormat::U8(&[0xAE]))),
+            Err(DisplayError::BusWriteError)
+        ));
+        assert!(matches!(
+            block_on(interface.send_data(DataFormat::U8(&[1]))),
+            Err(DisplayError::BusWriteError)
+        ));
+        // nothing is sent for the formats without a byte representation
+        assert!(matches!(
+            block_on(interface.send_data(DataFormat::U16(&[1]))),
+            Err(DisplayError::DataFormatNotImplemented)
+        ));
+    }
+}
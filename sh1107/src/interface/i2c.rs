@@ -0,0 +1,523 @@
+use core::iter::once;
+
+use embedded_hal_async::i2c::{I2c, Operation, SevenBitAddress};
+use i2c_write_iter::non_blocking::WriteIter;
+use itertools::Itertools;
+
+use super::{fill, Interface, ReadInterface};
+use crate::Error;
+
+/// 7-bit I²C address of the controller when SA0 is tied low.
+pub const ADDRESS_SA0_LOW: SevenBitAddress = 0x3C;
+/// 7-bit I²C address of the controller when SA0 is tied high.
+pub const ADDRESS_SA0_HIGH: SevenBitAddress = 0x3D;
+
+/// Source of the I²C address of the controller.
+///
+/// It is either fixed at compile time with [`Fixed`] or selected at runtime with a
+/// [`SevenBitAddress`].
+pub trait Address {
+    fn get(&self) -> SevenBitAddress;
+}
+
+/// Address fixed at compile time.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fixed<const ADDRESS: SevenBitAddress>;
+
+impl<const ADDRESS: SevenBitAddress> Address for Fixed<ADDRESS> {
+    fn get(&self) -> SevenBitAddress {
+        ADDRESS
+    }
+}
+
+impl Address for SevenBitAddress {
+    fn get(&self) -> SevenBitAddress {
+        *self
+    }
+}
+
+/// I²C interface.
+///
+/// Each transaction starts with a control byte: `0x00` for a stream of commands, `0x40` for a
+/// stream of data. Commands followed by data are sent in a single transaction where each command
+/// byte is preceded by `0x80`.
+pub struct I2cInterface<T, A> {
+    i2c: T,
+    address: A,
+}
+
+impl<T, const ADDRESS: SevenBitAddress> I2cInterface<T, Fixed<ADDRESS>> {
+    pub fn new(i2c: T) -> Self {
+        Self {
+            i2c,
+            address: Fixed,
+        }
+    }
+}
+
+impl<T> I2cInterface<T, SevenBitAddress> {
+    pub fn with_address(i2c: T, address: SevenBitAddress) -> Self {
+        Self { i2c, address }
+    }
+
+    pub fn set_address(&mut self, address: SevenBitAddress) {
+        self.address = address;
+    }
+}
+
+impl<T, A: Address> I2cInterface<T, A> {
+    pub fn address(&self) -> SevenBitAddress {
+        self.address.get()
+    }
+
+    pub fn release(self) -> T {
+        self.i2c
+    }
+}
+
+impl<T, A> Interface for I2cInterface<T, A>
+where
+    T: WriteIter<SevenBitAddress>,
+    A: Address,
+{
+    type Error = T::Error;
+
+    fn classify(error: Self::Error) -> Error<Self::Error> {
+        Error::from_i2c(error)
+    }
+
+    async fn write_commands(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        self.i2c
+            .write_iter(self.address.get(), once(0x00).chain(commands))
+            .await
+    }
+
+    async fn write_data(&mut self, data: impl Iterator<Item = u8>) -> Result<(), Self::Error> {
+        self.i2c
+            .write_iter(
+                self.address.get(), // Write data, no other control byte
+                once(0x40).chain(data),
+            )
+            .await
+    }
+
+    async fn write_commands_then_data(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+        data: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        self.i2c
+            .write_iter(
+                self.address.get(),
+                // command phase
+                Iterator::chain(once(0x80), Itertools::intersperse(commands, 0x80))
+                    // transition to data phase
+                    .chain(once(0x40))
+                    .chain(data),
+            )
+            .await
+    }
+}
+
+impl<T, A> ReadInterface for I2cInterface<T, A>
+where
+    T: WriteIter<SevenBitAddress>,
+    A: Address,
+{
+    async fn read_data(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        read_data(&mut self.i2c, self.address.get(), buf).await
+    }
+
+    async fn read_status(&mut self) -> Result<u8, Self::Error> {
+        read_status(&mut self.i2c, self.address.get()).await
+    }
+}
+
+/// I²C interface for buses that do not implement [`WriteIter`].
+///
+/// The command and data streams are copied to a buffer of `N` bytes, control byte included, and
+/// sent one buffer at a time. The framing is the same as [`I2cInterface`]'s, commands taking a
+/// parameter are never split across transactions.
+pub struct BufferedI2cInterface<T, A, const N: usize = 32> {
+    i2c: T,
+    address: A,
+}
+
+impl<T, const ADDRESS: SevenBitAddress, const N: usize> BufferedI2cInterface<T, Fixed<ADDRESS>, N> {
+    pub fn new(i2c: T) -> Self {
+        assert_buffer_len::<N>();
+        Self {
+            i2c,
+            address: Fixed,
+        }
+    }
+}
+
+impl<T, const N: usize> BufferedI2cInterface<T, SevenBitAddress, N> {
+    pub fn with_address(i2c: T, address: SevenBitAddress) -> Self {
+        assert_buffer_len::<N>();
+        Self { i2c, address }
+    }
+
+    pub fn set_address(&mut self, address: SevenBitAddress) {
+        self.address = address;
+    }
+}
+
+impl<T, A: Address, const N: usize> BufferedI2cInterface<T, A, N> {
+    pub fn address(&self) -> SevenBitAddress {
+        self.address.get()
+    }
+
+    pub fn release(self) -> T {
+        self.i2c
+    }
+}
+
+impl<T, A, const N: usize> Interface for BufferedI2cInterface<T, A, N>
+where
+    T: I2c<SevenBitAddress>,
+    A: Address,
+{
+    type Error = T::Error;
+
+    fn classify(error: Self::Error) -> Error<Self::Error> {
+        Error::from_i2c(error)
+    }
+
+    async fn write_commands(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        let mut buf = [0u8; N];
+        let mut len = 1;
+        let mut parameter = false;
+        for byte in commands {
+            let needed = if !parameter && takes_parameter(byte) {
+                2
+            } else {
+                1
+            };
+            if len + needed > N {
+                self.i2c.write(self.address.get(), &buf[..len]).await?;
+                len = 1;
+            }
+            buf[len] = byte;
+            len += 1;
+            parameter = !parameter && takes_parameter(byte);
+        }
+        if len > 1 {
+            self.i2c.write(self.address.get(), &buf[..len]).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_data(&mut self, mut data: impl Iterator<Item = u8>) -> Result<(), Self::Error> {
+        let mut buf = [0u8; N];
+        buf[0] = 0x40;
+        loop {
+            let len = fill(&mut buf[1..], &mut data);
+            if len == 0 {
+                return Ok(());
+            }
+            self.i2c.write(self.address.get(), &buf[..=len]).await?;
+            if len < N - 1 {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn write_commands_then_data(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+        mut data: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        let mut buf = [0u8; N];
+        let mut len = 0;
+        let mut parameter = false;
+        for byte in commands {
+            let needed = if !parameter && takes_parameter(byte) {
+                4
+            } else {
+                2
+            };
+            if len + needed > N {
+                self.i2c.write(self.address.get(), &buf[..len]).await?;
+                len = 0;
+            }
+            buf[len] = 0x80;
+            buf[len + 1] = byte;
+            len += 2;
+            parameter = !parameter && takes_parameter(byte);
+        }
+
+        // start the data phase in the same transaction if there is room left
+        if len + 1 < N {
+            buf[len] = 0x40;
+            let filled = fill(&mut buf[len + 1..], &mut data);
+            if filled != 0 {
+                self.i2c
+                    .write(self.address.get(), &buf[..len + 1 + filled])
+                    .await?;
+                if len + 1 + filled < N {
+                    return Ok(());
+                }
+                return self.write_data(data).await;
+            }
+        }
+        if len != 0 {
+            self.i2c.write(self.address.get(), &buf[..len]).await?;
+        }
+        self.write_data(data).await
+    }
+}
+
+impl<T, A, const N: usize> ReadInterface for BufferedI2cInterface<T, A, N>
+where
+    T: I2c<SevenBitAddress>,
+    A: Address,
+{
+    async fn read_data(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        read_data(&mut self.i2c, self.address.get(), buf).await
+    }
+
+    async fn read_status(&mut self) -> Result<u8, Self::Error> {
+        read_status(&mut self.i2c, self.address.get()).await
+    }
+}
+
+/// A command with its parameter takes 4 bytes once framed with `0x80` control bytes.
+const fn assert_buffer_len<const N: usize>() {
+    const { assert!(N >= 4, "the buffer must hold at least 4 bytes") }
+}
+
+/// Whether `opcode` is followed by a parameter byte.
+fn takes_parameter(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        0x81 | 0xA8 | 0xAD | 0xD3 | 0xD5 | 0xD9 | 0xDB | 0xDC
+    )
+}
+
+async fn read_data<T: I2c<SevenBitAddress>>(
+    i2c: &mut T,
+    address: SevenBitAddress,
+    buf: &mut [u8],
+) -> Result<(), T::Error> {
+    let mut dummy = 0u8;
+    i2c.transaction(
+        address,
+        &mut [
+            Operation::Write(&[0x40]),
+            Operation::Read(core::slice::from_mut(&mut dummy)),
+            Operation::Read(buf),
+        ],
+    )
+    .await
+}
+
+async fn read_status<T: I2c<SevenBitAddress>>(
+    i2c: &mut T,
+    address: SevenBitAddress,
+) -> Result<u8, T::Error> {
+    let mut res = 0u8;
+    i2c.write_read(address, &[0x80], core::slice::from_mut(&mut res))
+        .await?;
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use embedded_hal_async::i2c::ErrorType;
+
+    use super::*;
+    use crate::blocking::block_on;
+
+    /// Records the content of every write transaction.
+    #[derive(Default)]
+    struct Recorder(Vec<Vec<u8>>);
+
+    impl ErrorType for Recorder {
+        type Error = core::convert::Infallible;
+    }
+
+    impl I2c for Recorder {
+        async fn transaction(
+            &mut self,
+            _address: SevenBitAddress,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for operation in operations {
+                if let Operation::Write(bytes) = operation {
+                    self.0.push(bytes.to_vec());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Answers reads with consecutive bytes, recording every operation.
+    #[derive(Default)]
+    struct Counter {
+        writes: Vec<Vec<u8>>,
+        reads: Vec<usize>,
+        next: u8,
+    }
+
+    impl ErrorType for Counter {
+        type Error = core::convert::Infallible;
+    }
+
+    impl I2c for Counter {
+        async fn transaction(
+            &mut self,
+            _address: SevenBitAddress,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for operation in operations {
+                match operation {
+                    Operation::Write(bytes) => self.writes.push(bytes.to_vec()),
+                    Operation::Read(buf) => {
+                        self.reads.push(buf.len());
+                        for byte in buf.iter_mut() {
+                            *byte = self.next;
+                            self.next += 1;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn commands<const N: usize>(commands: &[u8]) -> Vec<Vec<u8>> {
+        let mut interface = BufferedI2cInterface::<_, Fixed<0x3C>, N>::new(Recorder::default());
+        block_on(interface.write_commands(commands.iter().copied())).unwrap();
+        interface.release().0
+    }
+
+    fn data<const N: usize>(data: &[u8]) -> Vec<Vec<u8>> {
+        let mut interface = BufferedI2cInterface::<_, Fixed<0x3C>, N>::new(Recorder::default());
+        block_on(interface.write_data(data.iter().copied())).unwrap();
+        interface.release().0
+    }
+
+    fn commands_then_data<const N: usize>(commands: &[u8], data: &[u8]) -> Vec<Vec<u8>> {
+        let mut interface = BufferedI2cInterface::<_, Fixed<0x3C>, N>::new(Recorder::default());
+        block_on(
+            interface.write_commands_then_data(commands.iter().copied(), data.iter().copied()),
+        )
+        .unwrap();
+        interface.release().0
+    }
+
+    #[test]
+    fn commands_are_framed_with_0x00() {
+        assert_eq!(
+            commands::<32>(&[0xAE, 0x81, 0x7F, 0xAF]),
+            [[0x00, 0xAE, 0x81, 0x7F, 0xAF]]
+        );
+        assert_eq!(commands::<32>(&[]), Vec::<Vec<u8>>::new());
+    }
+
+    #[test]
+    fn commands_keep_their_parameter() {
+        // 0x81 would fit, but not its parameter
+        assert_eq!(
+            commands::<4>(&[0xAE, 0xAF, 0x81, 0x7F]),
+            [std::vec![0x00, 0xAE, 0xAF], std::vec![0x00, 0x81, 0x7F],]
+        );
+        assert_eq!(
+            commands::<5>(&[0xAE, 0xAF, 0x81, 0x7F]),
+            [[0x00, 0xAE, 0xAF, 0x81, 0x7F]]
+        );
+        // a parameter that looks like an opcode taking a parameter is not mistaken for one
+        assert_eq!(
+            commands::<4>(&[0x81, 0x81, 0xAF]),
+            [[0x00, 0x81, 0x81, 0xAF]]
+        );
+    }
+
+    #[test]
+    fn data_is_framed_with_0x40() {
+        assert_eq!(
+            data::<4>(&[1, 2, 3, 4]),
+            [std::vec![0x40, 1, 2, 3], std::vec![0x40, 4],]
+        );
+        assert_eq!(
+            data::<4>(&[1, 2, 3, 4, 5, 6]),
+            [[0x40, 1, 2, 3], [0x40, 4, 5, 6],]
+        );
+        assert_eq!(data::<4>(&[]), Vec::<Vec<u8>>::new());
+    }
+
+    #[test]
+    fn commands_then_data_are_framed_with_0x80_then_0x40() {
+        assert_eq!(
+            commands_then_data::<8>(&[0xB0, 0x81, 0x7F], &[1, 2, 3]),
+            [
+                std::vec![0x80, 0xB0, 0x80, 0x81, 0x80, 0x7F, 0x40, 1],
+                std::vec![0x40, 2, 3],
+            ]
+        );
+        assert_eq!(
+            commands_then_data::<32>(&[0xB0], &[1, 2]),
+            [[0x80, 0xB0, 0x40, 1, 2]]
+        );
+        assert_eq!(commands_then_data::<32>(&[0xB0], &[]), [[0x80, 0xB0]]);
+    }
+
+    #[test]
+    fn commands_then_data_keep_their_parameter() {
+        assert_eq!(
+            commands_then_data::<4>(&[0xAE, 0x81, 0x7F], &[1]),
+            [
+                std::vec![0x80, 0xAE],
+                std::vec![0x80, 0x81, 0x80, 0x7F],
+                std::vec![0x40, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn data_phase_needs_room_for_a_data_byte() {
+        // `len + 1 == N`: the 0x40 control byte would fit, but no data after it
+        assert_eq!(
+            commands_then_data::<7>(&[0xAE, 0x81, 0x7F], &[1, 2]),
+            [
+                std::vec![0x80, 0xAE, 0x80, 0x81, 0x80, 0x7F],
+                std::vec![0x40, 1, 2],
+            ]
+        );
+        // `len + 1 < N`: the first data byte shares the transaction
+        assert_eq!(
+            commands_then_data::<8>(&[0xAE, 0x81, 0x7F], &[1, 2]),
+            [
+                std::vec![0x80, 0xAE, 0x80, 0x81, 0x80, 0x7F, 0x40, 1],
+                std::vec![0x40, 2],
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_discard_the_dummy_byte() {
+        let mut interface = BufferedI2cInterface::<_, Fixed<0x3C>>::new(Counter::default());
+        let mut buf = [0xFF; 3];
+        block_on(interface.read_data(&mut buf)).unwrap();
+        // the dummy byte got 0
+        assert_eq!(buf, [1, 2, 3]);
+        block_on(interface.read_data(&mut buf)).unwrap();
+        assert_eq!(buf, [5, 6, 7]);
+        let counter = interface.release();
+        assert_eq!(counter.writes, [[0x40], [0x40]]);
+        assert_eq!(counter.reads, [1, 3, 1, 3]);
+    }
+}
@@ -0,0 +1,78 @@
+//! Transports the command and data streams to the controller.
+//!
+//! [`Sh1107`](crate::Sh1107) encodes the commands and hands the resulting bytes to an
+//! [`Interface`], which takes care of the framing required by the bus.
+
+use crate::Error;
+
+#[cfg(feature = "display-interface")]
+mod display_interface;
+mod i2c;
+mod parallel;
+mod spi;
+mod spi3;
+
+#[cfg(feature = "display-interface")]
+pub use display_interface::DataCommandInterface;
+pub use i2c::{
+    Address, BufferedI2cInterface, Fixed, I2cInterface, ADDRESS_SA0_HIGH, ADDRESS_SA0_LOW,
+};
+pub use parallel::{BusCycle, DataBus, Intel8080, Motorola6800, ParallelError, ParallelInterface};
+pub use spi::{SpiError, SpiInterface};
+pub use spi3::{PackedSpi3WireInterface, Spi3WireInterface};
+
+/// Write access to the controller.
+#[allow(async_fn_in_trait)]
+pub trait Interface {
+    type Error;
+
+    /// Turns an error reported by the bus into a driver error.
+    ///
+    /// Every error is reported as [`Error::Bus`] unless the interface overrides it.
+    fn classify(error: Self::Error) -> Error<Self::Error> {
+        Error::Bus(error)
+    }
+
+    /// Sends encoded commands.
+    async fn write_commands(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error>;
+
+    /// Sends data to the display RAM.
+    async fn write_data(&mut self, data: impl Iterator<Item = u8>) -> Result<(), Self::Error>;
+
+    /// Sends encoded commands followed by data to the display RAM.
+    ///
+    /// Interfaces that can do so send both in a single transaction.
+    async fn write_commands_then_data(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+        data: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        self.write_commands(commands).await?;
+        self.write_data(data).await
+    }
+}
+
+/// Read access to the controller.
+///
+/// Not all interfaces support reading, the serial interfaces for instance are write only.
+#[allow(async_fn_in_trait)]
+pub trait ReadInterface: Interface {
+    /// Reads `buf.len()` bytes from the display RAM, starting at the current address.
+    ///
+    /// The controller outputs a dummy byte before the content of the RAM, implementations
+    /// discard it.
+    async fn read_data(&mut self, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    async fn read_status(&mut self) -> Result<u8, Self::Error>;
+}
+
+/// Fills `buf` from `items` and returns the number of items written.
+pub(crate) fn fill<T>(buf: &mut [T], items: &mut impl Iterator<Item = T>) -> usize {
+    buf.iter_mut()
+        .zip(items)
+        .map(|(slot, item)| *slot = item)
+        .count()
+}
@@ -0,0 +1,529 @@
+use embedded_hal::digital::{InputPin, OutputPin, PinState};
+
+use super::{Interface, ReadInterface};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ParallelError<B, P> {
+    /// The data bus could not be driven or sampled.
+    Bus(B),
+    /// A control pin could not be driven.
+    Pin(P),
+}
+
+/// 8-bit data bus (D0 to D7) of the parallel interface.
+pub trait DataBus {
+    type Error;
+
+    /// Drives `byte` on the data lines.
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error>;
+
+    /// Releases the data lines and samples them.
+    fn read(&mut self) -> Result<u8, Self::Error>;
+}
+
+/// Data bus made of 8 GPIOs, D0 first.
+///
+/// Reading releases the lines by driving them high, the pins must therefore be configured as open
+/// drain outputs with pull-ups.
+impl<P: InputPin + OutputPin> DataBus for [P; 8] {
+    type Error = P::Error;
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        for (bit, pin) in self.iter_mut().enumerate() {
+            pin.set_state(PinState::from((byte & (1 << bit)) != 0))?;
+        }
+        Ok(())
+    }
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        for pin in self.iter_mut() {
+            pin.set_high()?;
+        }
+        let mut byte = 0;
+        for (bit, pin) in self.iter_mut().enumerate() {
+            if pin.is_high()? {
+                byte |= 1 << bit;
+            }
+        }
+        Ok(byte)
+    }
+}
+
+/// Strobe protocol of the parallel interface.
+pub trait BusCycle {
+    type Error;
+
+    fn write<B: DataBus>(
+        &mut self,
+        bus: &mut B,
+        byte: u8,
+    ) -> Result<(), ParallelError<B::Error, Self::Error>>;
+
+    fn read<B: DataBus>(&mut self, bus: &mut B)
+        -> Result<u8, ParallelError<B::Error, Self::Error>>;
+}
+
+/// 8080-series bus: data is latched on the rising edge of WR#, and output while RD# is low.
+///
+/// WR# and RD# must be idle (high) when handed over.
+pub struct Intel8080<WR, RD> {
+    wr: WR,
+    rd: RD,
+}
+
+impl<WR, RD> Intel8080<WR, RD>
+where
+    WR: OutputPin,
+    RD: OutputPin<Error = WR::Error>,
+{
+    pub fn new(wr: WR, rd: RD) -> Self {
+        Self { wr, rd }
+    }
+
+    pub fn release(self) -> (WR, RD) {
+        (self.wr, self.rd)
+    }
+}
+
+impl<WR, RD> BusCycle for Intel8080<WR, RD>
+where
+    WR: OutputPin,
+    RD: OutputPin<Error = WR::Error>,
+{
+    type Error = WR::Error;
+
+    fn write<B: DataBus>(
+        &mut self,
+        bus: &mut B,
+        byte: u8,
+    ) -> Result<(), ParallelError<B::Error, Self::Error>> {
+        self.wr.set_low().map_err(ParallelError::Pin)?;
+        bus.write(byte).map_err(ParallelError::Bus)?;
+        self.wr.set_high().map_err(ParallelError::Pin)
+    }
+
+    fn read<B: DataBus>(
+        &mut self,
+        bus: &mut B,
+    ) -> Result<u8, ParallelError<B::Error, Self::Error>> {
+        self.rd.set_low().map_err(ParallelError::Pin)?;
+        let byte = bus.read().map_err(ParallelError::Bus);
+        let released = self.rd.set_high().map_err(ParallelError::Pin);
+        byte.and_then(|byte| released.map(|()| byte))
+    }
+}
+
+/// 6800-series bus: R/W# selects the direction, data is latched on the falling edge of E, and
+/// output while E is high.
+///
+/// E must be idle (low) when handed over.
+pub struct Motorola6800<RW, E> {
+    rw: RW,
+    e: E,
+}
+
+impl<RW, E> Motorola6800<RW, E>
+where
+    RW: OutputPin,
+    E: OutputPin<Error = RW::Error>,
+{
+    pub fn new(rw: RW, e: E) -> Self {
+        Self { rw, e }
+    }
+
+    pub fn release(self) -> (RW, E) {
+        (self.rw, self.e)
+    }
+}
+
+impl<RW, E> BusCycle for Motorola6800<RW, E>
+where
+    RW: OutputPin,
+    E: OutputPin<Error = RW::Error>,
+{
+    type Error = RW::Error;
+
+    fn write<B: DataBus>(
+        &mut self,
+        bus: &mut B,
+        byte: u8,
+    ) -> Result<(), ParallelError<B::Error, Self::Error>> {
+        self.rw.set_low().map_err(ParallelError::Pin)?;
+        bus.write(byte).map_err(ParallelError::Bus)?;
+        self.e.set_high().map_err(ParallelError::Pin)?;
+        self.e.set_low().map_err(ParallelError::Pin)
+    }
+
+    fn read<B: DataBus>(
+        &mut self,
+        bus: &mut B,
+    ) -> Result<u8, ParallelError<B::Error, Self::Error>> {
+        self.rw.set_high().map_err(ParallelError::Pin)?;
+        self.e.set_high().map_err(ParallelError::Pin)?;
+        let byte = bus.read().map_err(ParallelError::Bus);
+        let released = self.e.set_low().map_err(ParallelError::Pin);
+        byte.and_then(|byte| released.map(|()| byte))
+    }
+}
+
+/// 8-bit parallel interface driven by GPIOs.
+///
+/// CS# is asserted for the duration of each operation, D/C# is low for commands and status reads
+/// and high for display RAM accesses.
+pub struct ParallelInterface<BUS, C, DC, CS> {
+    bus: BUS,
+    cycle: C,
+    dc: DC,
+    cs: CS,
+}
+
+impl<BUS, C, DC, CS> ParallelInterface<BUS, C, DC, CS>
+where
+    BUS: DataBus,
+    C: BusCycle,
+    DC: OutputPin<Error = C::Error>,
+    CS: OutputPin<Error = C::Error>,
+{
+    pub fn new(bus: BUS, cycle: C, dc: DC, cs: CS) -> Self {
+        Self { bus, cycle, dc, cs }
+    }
+
+    pub fn release(self) -> (BUS, C, DC, CS) {
+        (self.bus, self.cycle, self.dc, self.cs)
+    }
+
+    /// Runs `f` with CS# asserted, CS# is released even if `f` fails.
+    ///
+    /// An error of `f` takes precedence over a failure to release CS#.
+    fn selected<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<R, ParallelError<BUS::Error, C::Error>>,
+    ) -> Result<R, ParallelError<BUS::Error, C::Error>> {
+        self.cs.set_low().map_err(ParallelError::Pin)?;
+        let res = f(self);
+        let released = self.cs.set_high().map_err(ParallelError::Pin);
+        res.and_then(|value| released.map(|()| value))
+    }
+
+    fn write(
+        &mut self,
+        dc: PinState,
+        bytes: impl Iterator<Item = u8>,
+    ) -> Result<(), ParallelError<BUS::Error, C::Error>> {
+        self.dc.set_state(dc).map_err(ParallelError::Pin)?;
+        bytes
+            .into_iter()
+            .try_for_each(|byte| self.cycle.write(&mut self.bus, byte))
+    }
+}
+
+impl<BUS, C, DC, CS> Interface for ParallelInterface<BUS, C, DC, CS>
+where
+    BUS: DataBus,
+    C: BusCycle,
+    DC: OutputPin<Error = C::Error>,
+    CS: OutputPin<Error = C::Error>,
+{
+    type Error = ParallelError<BUS::Error, C::Error>;
+
+    async fn write_commands(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        self.selected(|this| this.write(PinState::Low, commands))
+    }
+
+    async fn write_data(&mut self, data: impl Iterator<Item = u8>) -> Result<(), Self::Error> {
+        self.selected(|this| this.write(PinState::High, data))
+    }
+
+    async fn write_commands_then_data(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+        data: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        self.selected(|this| {
+            this.write(PinState::Low, commands)?;
+            this.write(PinState::High, data)
+        })
+    }
+}
+
+impl<BUS, C, DC, CS> ReadInterface for ParallelInterface<BUS, C, DC, CS>
+where
+    BUS: DataBus,
+    C: BusCycle,
+    DC: OutputPin<Error = C::Error>,
+    CS: OutputPin<Error = C::Error>,
+{
+    async fn read_data(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.selected(|this| {
+            this.dc.set_high().map_err(ParallelError::Pin)?;
+            // dummy read
+            this.cycle.read(&mut this.bus)?;
+            for byte in buf.iter_mut() {
+                *byte = this.cycle.read(&mut this.bus)?;
+            }
+            Ok(())
+        })
+    }
+
+    async fn read_status(&mut self) -> Result<u8, Self::Error> {
+        self.selected(|this| {
+            this.dc.set_low().map_err(ParallelError::Pin)?;
+            this.cycle.read(&mut this.bus)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::cell::RefCell;
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    use embedded_hal::digital::{ErrorKind, ErrorType};
+
+    use super::*;
+    use crate::blocking::block_on;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    enum Event {
+        Low(&'static str),
+        High(&'static str),
+        Write(u8),
+        Read(u8),
+    }
+
+    type Log = Rc<RefCell<Vec<Event>>>;
+
+    /// Control pin logging its level changes, driving it high fails when `fail_high` is set.
+    struct Pin {
+        name: &'static str,
+        log: Log,
+        fail_high: bool,
+    }
+
+    impl ErrorType for Pin {
+        type Error = ErrorKind;
+    }
+
+    impl OutputPin for Pin {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.log.borrow_mut().push(Event::Low(self.name));
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            if self.fail_high {
+                return Err(ErrorKind::Other);
+            }
+            self.log.borrow_mut().push(Event::High(self.name));
+            Ok(())
+        }
+    }
+
+    /// Data bus logging the bytes written, and answering reads with `reads`.
+    struct Bus {
+        log: Log,
+        reads: Vec<u8>,
+        fail: bool,
+    }
+
+    impl DataBus for Bus {
+        type Error = ();
+
+        fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+            if self.fail {
+                return Err(());
+            }
+            self.log.borrow_mut().push(Event::Write(byte));
+            Ok(())
+        }
+
+        fn read(&mut self) -> Result<u8, Self::Error> {
+            let byte = self.reads.remove(0);
+            self.log.borrow_mut().push(Event::Read(byte));
+            Ok(byte)
+        }
+    }
+
+    fn pin(name: &'static str, log: &Log) -> Pin {
+        Pin {
+            name,
+            log: log.clone(),
+            fail_high: false,
+        }
+    }
+
+    fn bus(log: &Log, reads: &[u8]) -> Bus {
+        Bus {
+            log: log.clone(),
+            reads: reads.to_vec(),
+            fail: false,
+        }
+    }
+
+    fn intel8080(log: &Log, reads: &[u8]) -> ParallelInterface<Bus, Intel8080<Pin, Pin>, Pin, Pin> {
+        let cycle = Intel8080::new(pin("WR", log), pin("RD", log));
+        ParallelInterface::new(bus(log, reads), cycle, pin("DC", log), pin("CS", log))
+    }
+
+    fn motorola6800(
+        log: &Log,
+        reads: &[u8],
+    ) -> ParallelInterface<Bus, Motorola6800<Pin, Pin>, Pin, Pin> {
+        let cycle = Motorola6800::new(pin("RW", log), pin("E", log));
+        ParallelInterface::new(bus(log, reads), cycle, pin("DC", log), pin("CS", log))
+    }
+
+    /// Takes the events logged so far.
+    fn events(log: &Log) -> Vec<Event> {
+        log.take()
+    }
+
+    #[test]
+    fn intel8080_write_cycles() {
+        use Event::*;
+
+        let log = Log::default();
+        let mut interface = intel8080(&log, &[]);
+        block_on(interface.write_commands_then_data([0xAE].into_iter(), [0x12].into_iter()))
+            .unwrap();
+        assert_eq!(
+            events(&log),
+            [
+                Low("CS"),
+                Low("DC"),
+                Low("WR"),
+                Write(0xAE),
+                High("WR"),
+                High("DC"),
+                Low("WR"),
+                Write(0x12),
+                High("WR"),
+                High("CS"),
+            ]
+        );
+    }
+
+    #[test]
+    fn intel8080_read_cycles() {
+        use Event::*;
+
+        let log = Log::default();
+        let mut interface = intel8080(&log, &[0xFF, 1, 2, 0x40]);
+        let mut buf = [0; 2];
+        block_on(interface.read_data(&mut buf)).unwrap();
+        // the dummy byte is discarded
+        assert_eq!(buf, [1, 2]);
+        assert_eq!(
+            events(&log),
+            [
+                Low("CS"),
+                High("DC"),
+                Low("RD"),
+                Read(0xFF),
+                High("RD"),
+                Low("RD"),
+                Read(1),
+                High("RD"),
+                Low("RD"),
+                Read(2),
+                High("RD"),
+                High("CS"),
+            ]
+        );
+
+        assert_eq!(block_on(interface.read_status()), Ok(0x40));
+        assert_eq!(
+            events(&log),
+            [
+                Low("CS"),
+                Low("DC"),
+                Low("RD"),
+                Read(0x40),
+                High("RD"),
+                High("CS"),
+            ]
+        );
+    }
+
+    #[test]
+    fn motorola6800_write_cycles() {
+        use Event::*;
+
+        let log = Log::default();
+        let mut interface = motorola6800(&log, &[]);
+        block_on(interface.write_commands([0xAE].into_iter())).unwrap();
+        block_on(interface.write_data([0x12].into_iter())).unwrap();
+        assert_eq!(
+            events(&log),
+            [
+                Low("CS"),
+                Low("DC"),
+                Low("RW"),
+                Write(0xAE),
+                High("E"),
+                Low("E"),
+                High("CS"),
+                Low("CS"),
+                High("DC"),
+                Low("RW"),
+                Write(0x12),
+                High("E"),
+                Low("E"),
+                High("CS"),
+            ]
+        );
+    }
+
+    #[test]
+    fn motorola6800_read_cycles() {
+        use Event::*;
+
+        let log = Log::default();
+        let mut interface = motorola6800(&log, &[0xFF, 1]);
+        let mut buf = [0; 1];
+        block_on(interface.read_data(&mut buf)).unwrap();
+        assert_eq!(buf, [1]);
+        assert_eq!(
+            events(&log),
+            [
+                Low("CS"),
+                High("DC"),
+                High("RW"),
+                High("E"),
+                Read(0xFF),
+                Low("E"),
+                High("RW"),
+                High("E"),
+                Read(1),
+                Low("E"),
+                High("CS"),
+            ]
+        );
+    }
+
+    #[test]
+    fn transfer_error_takes_precedence_over_releasing_cs() {
+        let log = Log::default();
+        let mut interface = intel8080(&log, &[]);
+        interface.cs.fail_high = true;
+        interface.bus.fail = true;
+        assert_eq!(
+            block_on(interface.write_data([0x12].into_iter())),
+            Err(ParallelError::Bus(()))
+        );
+
+        // without a transfer error, failing to release CS# is reported
+        interface.bus.fail = false;
+        assert_eq!(
+            block_on(interface.write_data([0x12].into_iter())),
+            Err(ParallelError::Pin(ErrorKind::Other))
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiDevice;
+
+use super::{fill, Interface};
+
+/// Size of the buffer used to collect bytes before handing them to the SPI device.
+const CHUNK_LEN: usize = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpiError<S, P> {
+    Spi(S),
+    /// The D/C# pin could not be driven.
+    Pin(P),
+}
+
+/// 4-wire SPI interface.
+///
+/// The D/C# pin is driven low while sending commands and high while sending data. The serial
+/// interface is write only.
+pub struct SpiInterface<SPI, DC> {
+    spi: SPI,
+    dc: DC,
+}
+
+impl<SPI, DC> SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    pub fn new(spi: SPI, dc: DC) -> Self {
+        Self { spi, dc }
+    }
+
+    pub fn release(self) -> (SPI, DC) {
+        (self.spi, self.dc)
+    }
+
+    async fn write(
+        &mut self,
+        mut bytes: impl Iterator<Item = u8>,
+    ) -> Result<(), SpiError<SPI::Error, DC::Error>> {
+        let mut buf = [0; CHUNK_LEN];
+        loop {
+            let len = fill(&mut buf, &mut bytes);
+            if len == 0 {
+                return Ok(());
+            }
+            self.spi.write(&buf[..len]).await.map_err(SpiError::Spi)?;
+        }
+    }
+}
+
+impl<SPI, DC> Interface for SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    type Error = SpiError<SPI::Error, DC::Error>;
+
+    async fn write_commands(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        self.dc.set_low().map_err(SpiError::Pin)?;
+        self.write(commands).await
+    }
+
+    async fn write_data(&mut self, data: impl Iterator<Item = u8>) -> Result<(), Self::Error> {
+        self.dc.set_high().map_err(SpiError::Pin)?;
+        self.write(data).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::cell::RefCell;
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    use embedded_hal_async::spi::Operation;
+
+    use super::*;
+    use crate::blocking::block_on;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    enum Event {
+        Dc(bool),
+        Write(Vec<u8>),
+    }
+
+    type Log = Rc<RefCell<Vec<Event>>>;
+
+    struct Device(Log);
+
+    impl embedded_hal_async::spi::ErrorType for Device {
+        type Error = embedded_hal::spi::ErrorKind;
+    }
+
+    impl SpiDevice for Device {
+        async fn transaction(
+            &mut self,
+            operations: &mut [Operation<'_, u8>],
+        ) -> Result<(), Self::Error> {
+            for operation in operations {
+                match operation {
+                    Operation::Write(bytes) => {
+                        self.0.borrow_mut().push(Event::Write(bytes.to_vec()))
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct Dc(Log);
+
+    impl embedded_hal::digital::ErrorType for Dc {
+        type Error = embedded_hal::digital::ErrorKind;
+    }
+
+    impl OutputPin for Dc {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().push(Event::Dc(false));
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.0.borrow_mut().push(Event::Dc(true));
+            Ok(())
+        }
+    }
+
+    fn interface(log: &Log) -> SpiInterface<Device, Dc> {
+        SpiInterface::new(Device(log.clone()), Dc(log.clone()))
+    }
+
+    #[test]
+    fn dc_is_low_for_commands_and_high_for_data() {
+        use Event::*;
+
+        let log = Log::default();
+        let mut interface = interface(&log);
+        block_on(interface.write_commands([0xAE, 0xAF].into_iter())).unwrap();
+        block_on(interface.write_data([1, 2].into_iter())).unwrap();
+        assert_eq!(
+            log.take(),
+            [
+                Dc(false),
+                Write(std::vec![0xAE, 0xAF]),
+                Dc(true),
+                Write(std::vec![1, 2])
+            ]
+        );
+    }
+
+    #[test]
+    fn dc_toggles_between_commands_and_data() {
+        use Event::*;
+
+        let log = Log::default();
+        let mut interface = interface(&log);
+        block_on(interface.write_commands_then_data([0xB0].into_iter(), [1, 2, 3].into_iter()))
+            .unwrap();
+        assert_eq!(
+            log.take(),
+            [
+                Dc(false),
+                Write(std::vec![0xB0]),
+                Dc(true),
+                Write(std::vec![1, 2, 3])
+            ]
+        );
+    }
+
+    #[test]
+    fn long_transfers_are_split_in_chunks() {
+        use Event::*;
+
+        let log = Log::default();
+        let mut interface = interface(&log);
+        let data: Vec<u8> = (0..70).collect();
+        block_on(interface.write_data(data.iter().copied())).unwrap();
+        assert_eq!(
+            log.take(),
+            [
+                Dc(true),
+                Write(data[..CHUNK_LEN].to_vec()),
+                Write(data[CHUNK_LEN..2 * CHUNK_LEN].to_vec()),
+                Write(data[2 * CHUNK_LEN..].to_vec()),
+            ]
+        );
+
+        // a transfer filling the last chunk is not followed by an empty one
+        block_on(interface.write_data(data[..2 * CHUNK_LEN].iter().copied())).unwrap();
+        assert_eq!(
+            log.take(),
+            [
+                Dc(true),
+                Write(data[..CHUNK_LEN].to_vec()),
+                Write(data[CHUNK_LEN..2 * CHUNK_LEN].to_vec()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use embedded_hal_async::spi::SpiDevice;
+
+use super::{fill, Interface};
+
+/// Number of bytes collected before handing them to the SPI device.
+const CHUNK_LEN: usize = 32;
+/// Size of `CHUNK_LEN` 9-bit frames packed into bytes.
+const PACKED_CHUNK_LEN: usize = (CHUNK_LEN * 9).div_ceil(8);
+
+const DATA: u16 = 0x100;
+
+/// 3-wire SPI interface for hosts supporting 9-bit words.
+///
+/// Each byte is sent as a 9-bit word whose most significant bit is the D/C# flag. The serial
+/// interface is write only.
+pub struct Spi3WireInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI: SpiDevice<u16>> Spi3WireInterface<SPI> {
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+
+    async fn write(&mut self, mut frames: impl Iterator<Item = u16>) -> Result<(), SPI::Error> {
+        let mut buf = [0u16; CHUNK_LEN];
+        loop {
+            let len = fill(&mut buf, &mut frames);
+            if len == 0 {
+                return Ok(());
+            }
+            self.spi.write(&buf[..len]).await?;
+        }
+    }
+}
+
+impl<SPI: SpiDevice<u16>> Interface for Spi3WireInterface<SPI> {
+    type Error = SPI::Error;
+
+    async fn write_commands(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        self.write(commands.map(u16::from)).await
+    }
+
+    async fn write_data(&mut self, data: impl Iterator<Item = u8>) -> Result<(), Self::Error> {
+        self.write(data.map(|byte| DATA | u16::from(byte))).await
+    }
+
+    async fn write_commands_then_data(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+        data: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        let data = data.map(|byte| DATA | u16::from(byte));
+        self.write(commands.map(u16::from).chain(data)).await
+    }
+}
+
+/// 3-wire SPI interface for hosts limited to 8-bit words.
+///
+/// The 9-bit frames are packed into a bit stream, most significant bit first. When a transfer does
+/// not end on a byte boundary it is padded with zeros, the controller drops this incomplete frame
+/// when chip select is released. The serial interface is write only.
+pub struct PackedSpi3WireInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI: SpiDevice> PackedSpi3WireInterface<SPI> {
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+
+    async fn write(&mut self, mut frames: impl Iterator<Item = u16>) -> Result<(), SPI::Error> {
+        let mut buf = [0u16; CHUNK_LEN];
+        let mut packed = [0; PACKED_CHUNK_LEN];
+        loop {
+            let len = fill(&mut buf, &mut frames);
+            if len == 0 {
+                return Ok(());
+            }
+            let len = pack(&buf[..len], &mut packed);
+            self.spi.write(&packed[..len]).await?;
+        }
+    }
+}
+
+impl<SPI: SpiDevice> Interface for PackedSpi3WireInterface<SPI> {
+    type Error = SPI::Error;
+
+    async fn write_commands(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        self.write(commands.map(u16::from)).await
+    }
+
+    async fn write_data(&mut self, data: impl Iterator<Item = u8>) -> Result<(), Self::Error> {
+        self.write(data.map(|byte| DATA | u16::from(byte))).await
+    }
+
+    async fn write_commands_then_data(
+        &mut self,
+        commands: impl Iterator<Item = u8>,
+        data: impl Iterator<Item = u8>,
+    ) -> Result<(), Self::Error> {
+        let data = data.map(|byte| DATA | u16::from(byte));
+        self.write(commands.map(u16::from).chain(data)).await
+    }
+}
+
+/// Packs 9-bit `frames` into `out` and returns the number of bytes written.
+fn pack(frames: &[u16], out: &mut [u8]) -> usize {
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut len = 0;
+    for &frame in frames {
+        acc = (acc << 9) | u32::from(frame & 0x1FF);
+        bits += 9;
+        while bits >= 8 {
+            bits -= 8;
+            out[len] = (acc >> bits) as u8;
+            len += 1;
+        }
+        acc &= (1 << bits) - 1;
+    }
+    if bits != 0 {
+        out[len] = (acc << (8 - bits)) as u8;
+        len += 1;
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_pads_with_zeros() {
+        let mut out = [0xAA; 4];
+        assert_eq!(pack(&[0x0AF], &mut out), 2);
+        assert_eq!(out[..2], [0b0101_0111, 0b1000_0000]);
+
+        // 0_1010_1110 1_0101_0101
+        assert_eq!(pack(&[0x0AE, DATA | 0x55], &mut out), 3);
+        assert_eq!(out[..3], [0b0101_0111, 0b0101_0101, 0b0100_0000]);
+    }
+
+    #[test]
+    fn pack_ignores_bits_above_the_frame() {
+        let mut out = [0; 2];
+        assert_eq!(pack(&[0xFE00 | DATA | 0xFF], &mut out), 2);
+        assert_eq!(out, [0xFF, 0x80]);
+    }
+
+    #[test]
+    fn pack_eight_frames_into_nine_bytes() {
+        let frames = [0x000, 0x1FF, 0x000, 0x1FF, 0x000, 0x1FF, 0x000, 0x1FF];
+        let mut out = [0; 10];
+        assert_eq!(pack(&frames, &mut out), 9);
+        assert_eq!(
+            out[..9],
+            [
+                0b0000_0000,
+                0b0111_1111,
+                0b1100_0000,
+                0b0001_1111,
+                0b1111_0000,
+                0b0000_0111,
+                0b1111_1100,
+                0b0000_0001,
+                0b1111_1111
+            ]
+        );
+    }
+
+    #[test]
+    fn pack_full_chunk_without_padding() {
+        let frames = [DATA | 0x5A; CHUNK_LEN];
+        let mut out = [0; PACKED_CHUNK_LEN];
+        assert_eq!(pack(&frames, &mut out), PACKED_CHUNK_LEN);
+        // 8 frames span 9 bytes, the pattern repeats
+        assert_eq!(out[..9], out[9..18]);
+    }
+}
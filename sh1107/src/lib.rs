@@ -2,11 +2,39 @@
 //! See the [datasheet](https://www.displayfuture.com/Display/datasheet/controller/SH1107.pdf) for
 //! further details
 
-use core::iter::once;
+use core::iter::Peekable;
+use core::num::NonZeroUsize;
+use core::ops::Range;
 
+use embedded_hal::digital::{Error as _, OutputPin};
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::i2c::SevenBitAddress;
 pub use i2c_write_iter::non_blocking::WriteIter;
-use itertools::Itertools;
+
+use decoder::{Decoder, Frame};
+use observer::{notify, Framing, NoObserver, Observer};
+pub use registers::Registers;
+use retry::{retry, NoRetry, RetryPolicy};
+pub use text::ParseError;
+pub use timing::{FrameTiming, NOMINAL_OSC_HZ};
+
+pub mod blocking;
+pub mod decoder;
+pub mod interface;
+pub mod observer;
+mod parameters;
+mod registers;
+pub mod retry;
+mod text;
+mod timing;
+pub mod wait;
+
+pub use interface::{
+    Address, BufferedI2cInterface, Fixed, I2cInterface, Interface, PackedSpi3WireInterface,
+    ParallelInterface, ReadInterface, Spi3WireInterface, SpiError, SpiInterface, ADDRESS_SA0_HIGH,
+    ADDRESS_SA0_LOW,
+};
+pub use parameters::{ChargePeriod, DcDcFrequency, DcDcSettings, OscFrequency, VcomhLevel};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -34,6 +62,42 @@ pub enum DisplayMode {
     WhiteOnBlack,
 }
 
+/// Content of the status register.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Status(u8);
+
+impl Status {
+    const BUSY: u8 = 0x80;
+    const OFF: u8 = 0x40;
+    const ID_MASK: u8 = 0x3F;
+    /// Identifier of the SH1107, in the bits below the flags.
+    const SH1107_ID: u8 = 0x07;
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// The controller is executing an internal operation and does not accept commands.
+    pub const fn is_busy(self) -> bool {
+        (self.0 & Self::BUSY) != 0
+    }
+    /// Fixed bits identifying the controller, 0x07 for the SH1107.
+    pub const fn id(self) -> u8 {
+        self.0 & Self::ID_MASK
+    }
+    pub const fn display_state(self) -> DisplayState {
+        if (self.0 & Self::OFF) != 0 {
+            DisplayState::Off
+        } else {
+            DisplayState::On
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Command {
@@ -42,8 +106,9 @@ pub enum Command {
     SetDisplayMode(DisplayMode),
     ForceEntireDisplay(bool),
     SetClkDividerOscFrequency {
+        /// Display clock divide ratio in [1; 16].
         divider: u8,
-        osc_freq_ratio: i8,
+        osc_freq: OscFrequency,
     },
     SetMultiplexRatio(u8),
     SetStartLine(u8),
@@ -53,11 +118,11 @@ pub enum Command {
     SetContrastControl(u8),
     /// Set Charge & Discharge period
     SetChargePeriods {
-        precharge: Option<u8>,
-        discharge: u8,
+        precharge: Option<ChargePeriod>,
+        discharge: ChargePeriod,
     },
-    SetVCOMHDeselectLevel(u8),
-    SetDCDCSettings(u8),
+    SetVCOMHDeselectLevel(VcomhLevel),
+    SetDCDCSettings(DcDcSettings),
     DisplayOnOff(DisplayState),
     SetPageAddress(u8),
     StartReadModifyWrite,
@@ -65,157 +130,2043 @@ pub enum Command {
     Nop,
 }
 
-impl Command {
-    fn encode(self) -> impl Iterator<Item = u8> {
-        use either::Either::*;
+/// A [`Command`] carries a parameter outside of the range accepted by the controller.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InvalidArgument(pub Command);
+
+impl core::fmt::Display for InvalidArgument {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid command parameter: {:?}", self.0)
+    }
+}
+
+impl core::error::Error for InvalidArgument {}
+
+/// Error returned by [`Sh1107`] operations.
+///
+/// Bus errors are classified by the [`Interface`], see [`Interface::classify`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E> {
+    /// The controller did not acknowledge its address: it is absent, unpowered or at another
+    /// address.
+    NotPresent(E),
+    /// Another controller took over the bus.
+    ArbitrationLoss(E),
+    /// Any other error reported by the underlying bus.
+    Bus(E),
+    /// A command was rejected before anything was sent to the controller.
+    InvalidArgument(InvalidArgument),
+    /// The controller was still busy when the deadline expired.
+    BusyTimeout,
+    /// The state read back from the controller differs from the expected one.
+    VerificationMismatch,
+    /// The reset pin could not be driven.
+    ResetPin(embedded_hal::digital::ErrorKind),
+    /// A device answered, but its status register does not identify an SH1107.
+    UnknownDevice(Status),
+    /// An area is empty or does not fit in the display.
+    InvalidArea,
+}
+
+impl<E: embedded_hal::i2c::Error> Error<E> {
+    /// Classifies an I²C error using [`embedded_hal::i2c::Error::kind`].
+    ///
+    /// A missing acknowledge of the address, or of an unknown source, is reported as
+    /// [`Error::NotPresent`].
+    pub fn from_i2c(error: E) -> Self {
+        use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+        match error.kind() {
+            ErrorKind::NoAcknowledge(
+                NoAcknowledgeSource::Address | NoAcknowledgeSource::Unknown,
+            ) => Self::NotPresent(error),
+            ErrorKind::ArbitrationLoss => Self::ArbitrationLoss(error),
+            _ => Self::Bus(error),
+        }
+    }
+}
+
+impl<E> From<InvalidArgument> for Error<E> {
+    fn from(value: InvalidArgument) -> Self {
+        Self::InvalidArgument(value)
+    }
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
-            Self::SetColumnAddress(addr) => {
-                assert!(addr < 128);
-                Right([addr & 0xF, 0x10 | ((addr & 0x70) >> 4)])
+            Self::NotPresent(e) => write!(f, "controller not present: {e:?}"),
+            Self::ArbitrationLoss(e) => write!(f, "bus arbitration lost: {e:?}"),
+            Self::Bus(e) => write!(f, "bus error: {e:?}"),
+            Self::InvalidArgument(e) => e.fmt(f),
+            Self::BusyTimeout => f.write_str("timed out waiting for the controller"),
+            Self::VerificationMismatch => f.write_str("controller state verification failed"),
+            Self::ResetPin(kind) => write!(f, "failed to drive the reset pin: {kind:?}"),
+            Self::UnknownDevice(status) => {
+                write!(f, "not an SH1107, status register {:#04x}", status.bits())
             }
+            Self::InvalidArea => f.write_str("area empty or out of the display"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug> core::error::Error for Error<E> {}
+
+/// Byte representation of a single [`Command`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EncodedCommand {
+    bytes: [u8; 2],
+    len: u8,
+}
+
+impl EncodedCommand {
+    const fn one(byte: u8) -> Self {
+        Self {
+            bytes: [byte, 0],
+            len: 1,
+        }
+    }
+    const fn two(bytes: [u8; 2]) -> Self {
+        Self { bytes, len: 2 }
+    }
+
+    pub const fn as_bytes(&self) -> &[u8] {
+        self.bytes.split_at(self.len as usize).0
+    }
+}
+
+impl IntoIterator for EncodedCommand {
+    type Item = u8;
+    type IntoIter = core::iter::Take<core::array::IntoIter<u8, 2>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.bytes.into_iter().take(self.len.into())
+    }
+}
+
+impl Command {
+    /// Checks that every parameter of the command is within the range accepted by the controller.
+    pub const fn validate(self) -> Result<(), InvalidArgument> {
+        let is_valid = match self {
+            Self::SetColumnAddress(addr) => addr < 128,
+            Self::SetMultiplexRatio(ratio) => 1 <= ratio && ratio <= 128,
+            Self::SetPageAddress(addr) => addr < 16,
+            Self::SetClkDividerOscFrequency { divider, .. } => 1 <= divider && divider <= 16,
+            Self::SetStartLine(line) => line < 128,
+            Self::SetDisplayOffset(offset) => offset < 128,
+            _ => true,
+        };
+        if is_valid {
+            Ok(())
+        } else {
+            Err(InvalidArgument(self))
+        }
+    }
+
+    /// Encodes the command into its byte representation.
+    pub const fn try_encode(self) -> Result<EncodedCommand, InvalidArgument> {
+        use EncodedCommand as E;
+
+        if let Err(e) = self.validate() {
+            return Err(e);
+        }
+        let encoded = match self {
+            Self::SetColumnAddress(addr) => E::two([addr & 0xF, 0x10 | ((addr & 0x70) >> 4)]),
             Self::SetAddressMode(mode) => {
-                Left(0x20 | if let AddressMode::Page = mode { 0 } else { 1 })
+                E::one(0x20 | if let AddressMode::Page = mode { 0 } else { 1 })
             }
-            Self::SetContrastControl(contrast) => Right([0x81, contrast]),
-            Self::SetSegmentReMap(is_remapped) => Left(0xA0 | if is_remapped { 1 } else { 0 }),
-            Self::SetMultiplexRatio(ratio) => {
-                assert!((1..=128).contains(&ratio));
-                Right([0xA8, ratio - 1])
-            }
-            Self::ForceEntireDisplay(state) => Left(0xA4 | if state { 1 } else { 0 }),
-            Self::SetDisplayMode(mode) => Left(
+            Self::SetContrastControl(contrast) => E::two([0x81, contrast]),
+            Self::SetSegmentReMap(is_remapped) => E::one(0xA0 | if is_remapped { 1 } else { 0 }),
+            Self::SetMultiplexRatio(ratio) => E::two([0xA8, ratio - 1]),
+            Self::ForceEntireDisplay(state) => E::one(0xA4 | if state { 1 } else { 0 }),
+            Self::SetDisplayMode(mode) => E::one(
                 0xA6 | if let DisplayMode::WhiteOnBlack = mode {
                     1
                 } else {
                     0
                 },
             ),
-            Self::SetDisplayOffset(offset) => Right([0xD3, offset & 0x7F]),
-            Self::SetDCDCSettings(cfg) => Right([0xAD, 0x80 | (cfg & 0x0F)]),
+            Self::SetDisplayOffset(offset) => E::two([0xD3, offset & 0x7F]),
+            Self::SetDCDCSettings(cfg) => E::two([0xAD, 0x80 | cfg.bits()]),
             Self::DisplayOnOff(state) => {
-                Left(0xAE | if let DisplayState::On = state { 1 } else { 0 })
-            }
-            Self::SetPageAddress(addr) => {
-                assert!(addr < 16);
-                Left(0xB0 | (addr & 0x0F))
+                E::one(0xAE | if let DisplayState::On = state { 1 } else { 0 })
             }
+            Self::SetPageAddress(addr) => E::one(0xB0 | (addr & 0x0F)),
             Self::SetCOMScanDirection(dir) => {
-                Left(0xC0 | if let Direction::Normal = dir { 0 } else { 0x08 })
+                E::one(0xC0 | if let Direction::Normal = dir { 0 } else { 0x08 })
             }
-            Self::SetClkDividerOscFrequency {
-                divider,
-                osc_freq_ratio,
-            } => {
-                assert!(
-                    osc_freq_ratio % 5 == 0,
-                    "osc_freq_ratio must be a multiple of 5."
-                );
-                assert!(
-                    (-25..=50).contains(&osc_freq_ratio),
-                    "osc_freq_ratio must be within [-25; 50]"
-                );
-                assert!((1..=16).contains(&divider), "divider must be in [1; 16]");
-
-                let osc_freq_ratio = osc_freq_ratio / 5 + 5;
-                Right([0xD5, ((osc_freq_ratio & 0xF) << 4) as u8 | (divider - 1)])
+            Self::SetClkDividerOscFrequency { divider, osc_freq } => {
+                E::two([0xD5, osc_freq.bits() << 4 | (divider - 1)])
             }
             Self::SetChargePeriods {
                 precharge,
                 discharge,
             } => {
-                let precharge = if let Some(v) = precharge {
-                    assert!((1..=15).contains(&v));
-                    v
-                } else {
-                    0
+                let precharge = match precharge {
+                    Some(v) => v.cycles(),
+                    None => 0,
                 };
-                assert!((1..=15).contains(&discharge));
-                let arg = discharge << 4 | precharge;
+                E::two([0xD9, discharge.cycles() << 4 | precharge])
+            }
+            Self::SetVCOMHDeselectLevel(level) => E::two([0xDB, level.bits()]),
+            Self::SetStartLine(line) => E::two([0xDC, line & 0x7F]),
+            Self::StartReadModifyWrite => E::one(0xE0),
+            Self::EndReadModifyWrite => E::one(0xEE),
+            Self::Nop => E::one(0xE3),
+        };
+        Ok(encoded)
+    }
+
+    /// Encodes a command that already went through [`Command::validate`].
+    fn encode(self) -> impl Iterator<Item = u8> {
+        self.try_encode().into_iter().flatten()
+    }
 
-                Right([0xD9, arg])
+    /// Number of bytes produced by [`Command::encode_all`] for this sequence.
+    ///
+    /// Panics if any command is invalid, which fails the build when evaluated in a const context.
+    pub const fn encoded_len(commands: &[Command]) -> usize {
+        let mut len = 0;
+        let mut i = 0;
+        while i < commands.len() {
+            len += match commands[i].try_encode() {
+                Ok(encoded) => encoded.len as usize,
+                Err(_) => panic!("invalid command parameter"),
+            };
+            i += 1;
+        }
+        len
+    }
+
+    /// Encodes a sequence of commands into a byte array.
+    ///
+    /// `N` must be equal to [`Command::encoded_len`]. The [`encode_commands!`] macro takes care of
+    /// this.
+    ///
+    /// Panics if any command is invalid, which fails the build when evaluated in a const context.
+    pub const fn encode_all<const N: usize>(commands: &[Command]) -> [u8; N] {
+        let mut out = [0; N];
+        let mut len = 0;
+        let mut i = 0;
+        while i < commands.len() {
+            let encoded = match commands[i].try_encode() {
+                Ok(encoded) => encoded,
+                Err(_) => panic!("invalid command parameter"),
+            };
+            let mut j = 0;
+            while j < encoded.len as usize {
+                assert!(len < N, "encoded commands do not fit in the output array");
+                out[len] = encoded.bytes[j];
+                len += 1;
+                j += 1;
             }
-            Self::SetVCOMHDeselectLevel(arg) => Right([0xDB, arg]),
-            Self::SetStartLine(line) => {
-                assert!(line < 128);
+            i += 1;
+        }
+        assert!(len == N, "encoded commands do not fill the output array");
+        out
+    }
+}
 
-                Right([0xDC, line & 0x7F])
+/// Encodes a sequence of [`Command`]s at compile time.
+///
+/// Evaluates to a `&'static [u8]` that can be sent with [`Sh1107::run_encoded`]. Out of range
+/// parameters are reported as a compilation error.
+///
+/// ```
+/// use sh1107::{encode_commands, Command};
+///
+/// const INIT: &[u8] = encode_commands![Command::SetMultiplexRatio(64), Command::SetStartLine(0)];
+/// assert_eq!(INIT, [0xA8, 63, 0xDC, 0]);
+/// ```
+///
+/// ```compile_fail
+/// use sh1107::{encode_commands, Command};
+///
+/// const INIT: &[u8] = encode_commands![Command::SetMultiplexRatio(0), Command::SetStartLine(0)];
+/// ```
+#[macro_export]
+macro_rules! encode_commands {
+    ($($command:expr),* $(,)?) => {{
+        const COMMANDS: &[$crate::Command] = &[$($command),*];
+        const ENCODED: [u8; $crate::Command::encoded_len(COMMANDS)] =
+            $crate::Command::encode_all(COMMANDS);
+        &ENCODED as &'static [u8]
+    }};
+}
+
+/// Position in the display RAM the controller writes to next.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Cursor {
+    mode: AddressMode,
+    column: u8,
+    page: u8,
+}
+
+impl Cursor {
+    /// Power-on state.
+    const RESET: Self = Self {
+        mode: AddressMode::Page,
+        column: 0,
+        page: 0,
+    };
+
+    fn apply(&mut self, command: Command) {
+        match command {
+            Command::SetAddressMode(mode) => self.mode = mode,
+            Command::SetColumnAddress(column) => self.column = column,
+            Command::SetPageAddress(page) => self.page = page,
+            _ => {}
+        }
+    }
+
+    fn apply_frame(&mut self, frame: Frame<'_>) {
+        match frame {
+            Frame::Command(command) => self.apply(command),
+            Frame::LowerColumnAddress(lower) => self.column = (self.column & 0x70) | lower,
+            Frame::HigherColumnAddress(higher) => {
+                self.column = (higher << 4) | (self.column & 0x0F)
             }
-            Self::StartReadModifyWrite => Left(0xE0),
-            Self::EndReadModifyWrite => Left(0xEE),
-            Self::Nop => Left(0xE3),
+            Frame::Data(_) => {}
+        }
+    }
+
+    /// Moves past `count` bytes of the display RAM.
+    fn advance(&mut self, count: usize) {
+        match self.mode {
+            AddressMode::Page => self.column = ((usize::from(self.column) + count) % 128) as u8,
+            AddressMode::Column => self.page = ((usize::from(self.page) + count) % 16) as u8,
+        }
+    }
+}
+
+/// Number of display RAM bytes staged per transaction when retries are enabled.
+const STAGING_LEN: usize = 64;
+
+/// Minimum duration of the reset pulse.
+const RESET_PULSE_US: u32 = 10;
+/// Time the controller takes to come out of reset.
+const RESET_TIME_US: u32 = 2;
+
+/// Driver for the controller, sitting on top of an [`Interface`].
+///
+/// `R` decides whether failed transactions are retried, see [`retry`].
+///
+/// `O` is called for every transaction put on the bus, see [`observer`].
+///
+/// The driver keeps a shadow of the controller configuration, see [`Self::registers`].
+pub struct Sh1107<DI, R = NoRetry, O = NoObserver> {
+    interface: DI,
+    retry: R,
+    observer: O,
+    cursor: Cursor,
+    registers: Registers,
+    /// Set by [`Self::sleep`]: whether it turned the DC-DC converter off.
+    asleep: Option<bool>,
+    max_transfer: Option<NonZeroUsize>,
+}
+
+impl<DI> Sh1107<DI> {
+    pub fn new(interface: DI) -> Self {
+        Self {
+            interface,
+            retry: NoRetry,
+            observer: NoObserver,
+            cursor: Cursor::RESET,
+            registers: Registers::UNKNOWN,
+            asleep: None,
+            max_transfer: None,
         }
-        .map_left(|v| [v])
-        .into_iter()
     }
 }
 
-pub struct Sh1107<T, const ADDRESS: SevenBitAddress>(T);
+impl<DI, R, O> Sh1107<DI, R, O> {
+    /// Retries failed transactions according to `retry`.
+    ///
+    /// With retries enabled, display RAM writes are staged in chunks of up to 64 bytes so that a
+    /// failed chunk can be sent again.
+    pub fn with_retry<R2: RetryPolicy>(self, retry: R2) -> Sh1107<DI, R2, O> {
+        Sh1107 {
+            interface: self.interface,
+            retry,
+            observer: self.observer,
+            cursor: self.cursor,
+            registers: self.registers,
+            asleep: self.asleep,
+            max_transfer: self.max_transfer,
+        }
+    }
+
+    pub fn retry_policy(&self) -> &R {
+        &self.retry
+    }
+
+    pub fn retry_policy_mut(&mut self) -> &mut R {
+        &mut self.retry
+    }
+
+    /// Reports every transaction to `observer`, each retry being a transaction of its own.
+    pub fn with_observer<O2>(self, observer: O2) -> Sh1107<DI, R, O2> {
+        Sh1107 {
+            interface: self.interface,
+            retry: self.retry,
+            observer,
+            cursor: self.cursor,
+            registers: self.registers,
+            asleep: self.asleep,
+            max_transfer: self.max_transfer,
+        }
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Limits the number of display RAM bytes sent in a single transaction, `None` by default.
+    ///
+    /// Longer writes are split, each chunk after the first one being preceded by the column and
+    /// page address it starts at. The interface adds its own framing on top of this limit: for
+    /// I²C, up to 7 bytes when the address is sent along with the data.
+    pub fn set_max_transfer(&mut self, max_transfer: Option<NonZeroUsize>) {
+        self.max_transfer = max_transfer;
+    }
+
+    pub fn max_transfer(&self) -> Option<NonZeroUsize> {
+        self.max_transfer
+    }
+
+    /// Controller configuration as set through this driver.
+    ///
+    /// [`Self::run`] skips the commands that would not change a known register.
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// Forgets the shadowed configuration, for instance after the controller was reset.
+    ///
+    /// The RAM address is assumed to be back to its power-on value.
+    pub fn invalidate_registers(&mut self) {
+        self.registers = Registers::UNKNOWN;
+        self.asleep = None;
+        self.cursor = Cursor::RESET;
+    }
+
+    /// Whether the controller was put to sleep with [`Self::sleep`].
+    pub fn is_asleep(&self) -> bool {
+        self.asleep.is_some()
+    }
+
+    pub fn interface(&self) -> &DI {
+        &self.interface
+    }
 
-impl<T, const ADDRESS: SevenBitAddress> Sh1107<T, ADDRESS>
+    pub fn interface_mut(&mut self) -> &mut DI {
+        &mut self.interface
+    }
+
+    pub fn release(self) -> DI {
+        self.interface
+    }
+}
+
+impl<T> Sh1107<I2cInterface<T, SevenBitAddress>>
 where
     T: WriteIter<SevenBitAddress>,
 {
-    pub fn new(i2c: T) -> Self {
-        Self(i2c)
+    /// Looks for a controller at [`ADDRESS_SA0_LOW`] then at [`ADDRESS_SA0_HIGH`].
+    ///
+    /// A controller is considered present when its status register reads back the identifier of
+    /// the SH1107, see [`Status::id`]. The second address is only tried when nothing acknowledged
+    /// the first one, other errors are returned right away. On failure, the bus is returned along
+    /// with the error.
+    pub async fn probe(i2c: T) -> Result<Self, (T, Error<T::Error>)> {
+        let mut sh1107 = Self::new(I2cInterface::with_address(i2c, ADDRESS_SA0_LOW));
+        match sh1107.identify().await {
+            Ok(()) => return Ok(sh1107),
+            Err(Error::NotPresent(_)) => {}
+            Err(e) => return Err((sh1107.release().release(), e)),
+        }
+
+        sh1107.interface.set_address(ADDRESS_SA0_HIGH);
+        match sh1107.identify().await {
+            Ok(()) => Ok(sh1107),
+            Err(e) => Err((sh1107.release().release(), e)),
+        }
+    }
+
+    async fn identify(&mut self) -> Result<(), Error<T::Error>> {
+        let status = self.status().await?;
+        if status.id() == Status::SH1107_ID {
+            Ok(())
+        } else {
+            Err(Error::UnknownDevice(status))
+        }
     }
+}
 
-    pub async fn run(
+/// Commands moving the RAM address to the position of `cursor`.
+fn address(cursor: Cursor) -> [Command; 2] {
+    [
+        Command::SetColumnAddress(cursor.column),
+        Command::SetPageAddress(cursor.page),
+    ]
+}
+
+/// Sends `commands` followed by `data`, or only `data` if there are no commands, and returns the
+/// number of data bytes sent.
+async fn write_observed<DI: Interface>(
+    interface: &mut DI,
+    observer: &mut impl Observer<DI::Error>,
+    commands: impl Iterator<Item = Command> + Clone,
+    data: impl Iterator<Item = u8>,
+) -> Result<usize, DI::Error> {
+    let mut count = 0;
+    let data = data.inspect(|_| count += 1);
+    let (framing, result) = if commands.clone().next().is_none() {
+        (Framing::Data, interface.write_data(data).await)
+    } else {
+        let encoded = commands.clone().flat_map(Command::encode);
+        let result = interface.write_commands_then_data(encoded, data).await;
+        (Framing::CommandsThenData, result)
+    };
+    notify(observer, framing, commands, count, &result);
+    result.map(|_| count)
+}
+
+impl<DI, R, O> Sh1107<DI, R, O>
+where
+    DI: Interface,
+    R: RetryPolicy,
+    O: Observer<DI::Error>,
+{
+    /// Resets the controller with its RES pin: 10 µs low, then 2 µs for the reset to complete.
+    ///
+    /// Every register goes back to its power-on value, [`Self::registers`] is invalidated.
+    pub async fn reset(
         &mut self,
-        commands: impl IntoIterator<Item = Command>,
-    ) -> Result<(), T::Error> {
-        self.0
-            .write_iter(
-                ADDRESS,
-                Iterator::chain(once(0x00), commands.into_iter().flat_map(Command::encode)),
+        reset: &mut impl OutputPin,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), Error<DI::Error>> {
+        reset.set_low().map_err(|e| Error::ResetPin(e.kind()))?;
+        delay.delay_us(RESET_PULSE_US).await;
+        reset.set_high().map_err(|e| Error::ResetPin(e.kind()))?;
+        delay.delay_us(RESET_TIME_US).await;
+        self.invalidate_registers();
+        Ok(())
+    }
+
+    /// Sends a sequence of commands.
+    ///
+    /// Every command is validated before anything is sent to the controller. Commands setting a
+    /// register of [`Self::registers`] to the value it already holds are skipped.
+    pub async fn run<C>(&mut self, commands: C) -> Result<(), Error<DI::Error>>
+    where
+        C: IntoIterator<Item = Command>,
+        C::IntoIter: Clone,
+    {
+        let commands = commands.into_iter();
+        commands.clone().try_for_each(Command::validate)?;
+
+        let mut shadow = self.registers;
+        let needed = commands.clone().filter(move |&command| {
+            let redundant = shadow.is_redundant(command);
+            shadow.apply(command);
+            !redundant
+        });
+        if needed.clone().next().is_some() {
+            let observer = &mut self.observer;
+            let result = retry(
+                &mut self.interface,
+                &mut self.retry,
+                async |interface, _| {
+                    let result = interface
+                        .write_commands(needed.clone().flat_map(Command::encode))
+                        .await;
+                    notify(observer, Framing::Commands, needed.clone(), 0, &result);
+                    result
+                },
             )
-            .await
+            .await;
+            self.update_registers(commands.clone(), result.is_ok());
+            result?;
+        }
+        commands.for_each(|command| self.cursor.apply(command));
+        Ok(())
     }
 
+    /// Records the effect of `commands` on the shadow, or forgets it if they may not have been
+    /// received.
+    fn update_registers(&mut self, commands: impl Iterator<Item = Command>, received: bool) {
+        for command in commands {
+            if received {
+                self.registers.apply(command);
+            } else {
+                self.registers.forget(command);
+            }
+        }
+    }
+
+    /// Sends commands pre-encoded with [`encode_commands!`] or [`Command::encode_all`].
+    ///
+    /// The commands are sent as is, [`Self::registers`] is updated accordingly. If some bytes cannot
+    /// be decoded, the registers are invalidated instead.
+    pub async fn run_encoded(&mut self, commands: &[u8]) -> Result<(), Error<DI::Error>> {
+        let undecoded = Decoder::commands(commands).any(|frame| frame.is_err());
+        let decoded = Decoder::commands(commands)
+            .map_while(Result::ok)
+            .filter_map(|frame| match frame {
+                Frame::Command(command) => Some(command),
+                _ => None,
+            });
+        let observer = &mut self.observer;
+        let result = retry(
+            &mut self.interface,
+            &mut self.retry,
+            async |interface, _| {
+                let result = interface.write_commands(commands.iter().copied()).await;
+                notify(observer, Framing::Commands, decoded.clone(), 0, &result);
+                result
+            },
+        )
+        .await;
+        if undecoded {
+            // what follows the first undecoded byte, and its effect on the controller, is unknown
+            self.invalidate_registers();
+            return result;
+        }
+        self.update_registers(decoded, result.is_ok());
+        result?;
+        Decoder::commands(commands)
+            .map_while(Result::ok)
+            .for_each(|frame| self.cursor.apply_frame(frame));
+        Ok(())
+    }
+
+    /// Turns the display off, then the DC-DC converter, following the power-down sequence of the
+    /// datasheet.
+    ///
+    /// The display RAM and the registers are retained. The converter is only turned off when its
+    /// settings are known, see [`Self::registers`]: otherwise it keeps running, send
+    /// [`Command::SetDCDCSettings`] beforehand to have it turned off as well.
+    pub async fn sleep(&mut self) -> Result<(), Error<DI::Error>> {
+        let dcdc = self
+            .registers
+            .dcdc_settings()
+            .filter(|settings| settings.enabled);
+        let dcdc_off = dcdc.map(|settings| {
+            Command::SetDCDCSettings(DcDcSettings {
+                enabled: false,
+                ..settings
+            })
+        });
+        self.run(
+            [Command::DisplayOnOff(DisplayState::Off)]
+                .into_iter()
+                .chain(dcdc_off),
+        )
+        .await?;
+        self.asleep = Some(self.asleep == Some(true) || dcdc.is_some());
+        Ok(())
+    }
+
+    /// Turns the DC-DC converter back on if [`Self::sleep`] turned it off, then the display.
+    ///
+    /// Every known register is sent again beforehand, in case the controller lost it while asleep.
+    /// Registers changed while asleep keep their new value.
+    pub async fn wake(&mut self) -> Result<(), Error<DI::Error>> {
+        let mut registers = self.registers;
+        if let (Some(true), Some(settings)) = (self.asleep, registers.dcdc_settings()) {
+            registers.apply(Command::SetDCDCSettings(DcDcSettings {
+                enabled: true,
+                ..settings
+            }));
+        }
+        self.registers = Registers::UNKNOWN;
+        self.run(
+            registers
+                .commands()
+                .chain([Command::DisplayOnOff(DisplayState::On)]),
+        )
+        .await?;
+        self.asleep = None;
+        Ok(())
+    }
+
+    /// Writes to the display RAM, starting at the current address.
+    ///
+    /// See [`Self::set_max_transfer`] for how long writes are split.
     pub async fn write_to_ram(
         &mut self,
         buf: impl IntoIterator<Item = u8>,
-    ) -> Result<(), T::Error> {
-        self.0
-            .write_iter(
-                ADDRESS, // Write data, no other control byte
-                once(0x40).chain(buf),
-            )
+    ) -> Result<(), Error<DI::Error>> {
+        self.write_chunks(None::<core::iter::Empty<Command>>, buf.into_iter())
             .await
     }
 
-    pub async fn run_then_write_to_ram(
+    /// Sends a sequence of commands followed by data, in a single transaction when the interface
+    /// supports it.
+    ///
+    /// Every command is validated before anything is sent to the controller.
+    pub async fn run_then_write_to_ram<C>(
         &mut self,
-        commands: impl IntoIterator<Item = Command>,
+        commands: C,
         data: impl IntoIterator<Item = u8>,
-    ) -> Result<(), T::Error> {
-        self.0
-            .write_iter(
-                ADDRESS,
-                // command phase
-                Iterator::chain(
-                    once(0x80),
-                    Itertools::intersperse(commands.into_iter().flat_map(Command::encode), 0x80),
-                )
-                // transition to data phase
-                .chain(once(0x40))
-                .chain(data.into_iter()),
+    ) -> Result<(), Error<DI::Error>>
+    where
+        C: IntoIterator<Item = Command>,
+        C::IntoIter: Clone,
+    {
+        let commands = commands.into_iter();
+        commands.clone().try_for_each(Command::validate)?;
+
+        self.write_chunks(Some(commands), data.into_iter()).await
+    }
+
+    fn chunk_len(&self) -> usize {
+        self.max_transfer.map_or(usize::MAX, NonZeroUsize::get)
+    }
+
+    /// Sends `commands` along with the first chunk of `data`, then the remaining chunks, each one
+    /// preceded by its address.
+    async fn write_chunks<C>(
+        &mut self,
+        mut commands: Option<C>,
+        data: impl Iterator<Item = u8>,
+    ) -> Result<(), Error<DI::Error>>
+    where
+        C: Iterator<Item = Command> + Clone,
+    {
+        let mut data = data.peekable();
+        let mut first = true;
+        loop {
+            let commands = commands.take();
+            let mut start = self.cursor;
+            commands
+                .clone()
+                .into_iter()
+                .flatten()
+                .for_each(|command| start.apply(command));
+
+            let sent = commands.clone();
+            let count = if self.retry.max_attempts() > 1 {
+                self.write_staged_chunk(commands, first, start, &mut data)
+                    .await
+            } else {
+                let readdress = commands.is_none() && !first;
+                let commands = commands
+                    .into_iter()
+                    .flatten()
+                    .chain(address(start).into_iter().filter(move |_| readdress));
+                let chunk = (&mut data).take(self.chunk_len());
+                write_observed(&mut self.interface, &mut self.observer, commands, chunk)
+                    .await
+                    .map_err(DI::classify)
+            };
+            self.update_registers(sent.into_iter().flatten(), count.is_ok());
+            let count = count?;
+            self.cursor = start;
+            self.cursor.advance(count);
+
+            first = false;
+            if data.peek().is_none() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Copies a chunk of `data` so that it can be sent again on failure, retries always send the
+    /// address of the chunk.
+    async fn write_staged_chunk<C>(
+        &mut self,
+        commands: Option<C>,
+        first: bool,
+        start: Cursor,
+        data: &mut Peekable<impl Iterator<Item = u8>>,
+    ) -> Result<usize, Error<DI::Error>>
+    where
+        C: Iterator<Item = Command> + Clone,
+    {
+        let mut buf = [0u8; STAGING_LEN];
+        let len = interface::fill(&mut buf[..self.chunk_len().min(STAGING_LEN)], data);
+        let chunk = &buf[..len];
+
+        let observer = &mut self.observer;
+        retry(
+            &mut self.interface,
+            &mut self.retry,
+            async |interface, attempt| {
+                let readdress = attempt > 1 || (commands.is_none() && !first);
+                let commands = commands
+                    .clone()
+                    .into_iter()
+                    .flatten()
+                    .chain(address(start).into_iter().filter(move |_| readdress));
+                write_observed(interface, observer, commands, chunk.iter().copied()).await
+            },
+        )
+        .await?;
+        Ok(len)
+    }
+}
+
+impl<DI, R, O> Sh1107<DI, R, O>
+where
+    DI: ReadInterface,
+    R: RetryPolicy,
+    O: Observer<DI::Error>,
+{
+    /// Reads `buf.len()` bytes from the display RAM, starting at the current address.
+    ///
+    /// The controller outputs a dummy byte before the content of the RAM, it is discarded. The
+    /// address moves past it too, it ends `buf.len() + 1` bytes further: a read right after another
+    /// one skips a byte.
+    pub async fn read_from_ram(&mut self, buf: &mut [u8]) -> Result<(), Error<DI::Error>> {
+        let start = self.cursor;
+        let observer = &mut self.observer;
+        retry(
+            &mut self.interface,
+            &mut self.retry,
+            async |interface, attempt| {
+                if attempt > 1 {
+                    let commands = address(start).into_iter();
+                    let result = interface
+                        .write_commands(commands.clone().flat_map(Command::encode))
+                        .await;
+                    notify(observer, Framing::Commands, commands, 0, &result);
+                    result?;
+                }
+                let result = interface.read_data(buf).await;
+                notify(
+                    observer,
+                    Framing::ReadData,
+                    core::iter::empty(),
+                    buf.len(),
+                    &result,
+                );
+                result
+            },
+        )
+        .await?;
+        self.cursor.advance(buf.len() + 1);
+        Ok(())
+    }
+
+    /// Reads a rectangle of the display RAM.
+    ///
+    /// In [`AddressMode::Page`], `buf` is filled page after page, each page holding
+    /// `columns.len()` bytes. In [`AddressMode::Column`], `buf` is filled column after column, each
+    /// column holding `pages.len()` bytes. Reading stops when `buf` is full.
+    pub async fn read_region(
+        &mut self,
+        mode: AddressMode,
+        pages: Range<u8>,
+        columns: Range<u8>,
+        buf: &mut [u8],
+    ) -> Result<(), Error<DI::Error>> {
+        if pages.is_empty() || columns.is_empty() {
+            return Ok(());
+        }
+
+        self.run([Command::SetAddressMode(mode)]).await?;
+        match mode {
+            AddressMode::Page => {
+                let first_column = columns.start;
+                for (page, line) in pages.zip(buf.chunks_mut(columns.len())) {
+                    self.run([
+                        Command::SetColumnAddress(first_column),
+                        Command::SetPageAddress(page),
+                    ])
+                    .await?;
+                    self.read_from_ram(line).await?;
+                }
+            }
+            AddressMode::Column => {
+                let first_page = pages.start;
+                for (column, line) in columns.zip(buf.chunks_mut(pages.len())) {
+                    self.run([
+                        Command::SetColumnAddress(column),
+                        Command::SetPageAddress(first_page),
+                    ])
+                    .await?;
+                    self.read_from_ram(line).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces each byte of `columns` in `page` with the result of `modify`, using the
+    /// read-modify-write mode of the controller.
+    ///
+    /// The session always ends with [`Command::EndReadModifyWrite`], which moves the column back to
+    /// `columns.start`. Reads are retried as they leave the column in place, writes are not.
+    pub async fn read_modify_write(
+        &mut self,
+        page: u8,
+        columns: Range<u8>,
+        mut modify: impl FnMut(u8) -> u8,
+    ) -> Result<(), Error<DI::Error>> {
+        if columns.is_empty() {
+            return Ok(());
+        }
+        Command::SetColumnAddress(columns.end - 1).validate()?;
+
+        self.run([
+            Command::SetAddressMode(AddressMode::Page),
+            Command::SetColumnAddress(columns.start),
+            Command::SetPageAddress(page),
+            Command::StartReadModifyWrite,
+        ])
+        .await?;
+
+        let mut result = Ok(());
+        for _ in columns {
+            result = self.modify_byte(&mut modify).await;
+            if result.is_err() {
+                break;
+            }
+        }
+        let end = self.run([Command::EndReadModifyWrite]).await;
+        result.and(end)
+    }
+
+    async fn modify_byte(
+        &mut self,
+        modify: &mut impl FnMut(u8) -> u8,
+    ) -> Result<(), Error<DI::Error>> {
+        let mut byte = [0];
+        let observer = &mut self.observer;
+        retry(
+            &mut self.interface,
+            &mut self.retry,
+            async |interface, _| {
+                let result = interface.read_data(&mut byte).await;
+                notify(observer, Framing::ReadData, core::iter::empty(), 1, &result);
+                result
+            },
+        )
+        .await?;
+
+        let data = core::iter::once(modify(byte[0]));
+        write_observed(
+            &mut self.interface,
+            &mut self.observer,
+            core::iter::empty(),
+            data,
+        )
+        .await
+        .map_err(DI::classify)?;
+        Ok(())
+    }
+
+    /// Reads the status register, the display state of [`Self::registers`] is updated from it.
+    pub async fn status(&mut self) -> Result<Status, Error<DI::Error>> {
+        let observer = &mut self.observer;
+        let bits = retry(
+            &mut self.interface,
+            &mut self.retry,
+            async |interface, _| {
+                let result = interface.read_status().await;
+                notify(
+                    observer,
+                    Framing::ReadStatus,
+                    core::iter::empty(),
+                    1,
+                    &result,
+                );
+                result
+            },
+        )
+        .await?;
+        let status = Status::from_bits(bits);
+        self.registers
+            .apply(Command::DisplayOnOff(status.display_state()));
+        Ok(status)
+    }
+
+    pub async fn is_busy(&mut self) -> Result<bool, Error<DI::Error>> {
+        Ok(self.status().await?.is_busy())
+    }
+
+    /// Polls the status register every `interval_us` until the controller is not busy.
+    ///
+    /// Fails with [`Error::BusyTimeout`] once the delays add up to `timeout_us`, the time spent
+    /// reading the status register is not accounted for. With a zero `interval_us`, the status
+    /// register is read up to `timeout_us` times after the first read.
+    pub async fn wait_until_ready(
+        &mut self,
+        delay: &mut impl DelayNs,
+        interval_us: u32,
+        timeout_us: u32,
+    ) -> Result<(), Error<DI::Error>> {
+        let mut polls = timeout_us.div_ceil(interval_us.max(1));
+        while self.is_busy().await? {
+            if polls == 0 {
+                return Err(Error::BusyTimeout);
+            }
+            polls -= 1;
+            delay.delay_us(interval_us).await;
+        }
+        Ok(())
+    }
+
+    /// Fails with [`Error::VerificationMismatch`] unless the status register reports `expected`.
+    ///
+    /// The shadowed display state follows the status register, so that sending `expected` again
+    /// after a mismatch is not skipped as redundant.
+    pub async fn verify_display_state(
+        &mut self,
+        expected: DisplayState,
+    ) -> Result<(), Error<DI::Error>> {
+        if self.status().await?.display_state() == expected {
+            Ok(())
+        } else {
+            Err(Error::VerificationMismatch)
+        }
+    }
+}
+
+/// Every command accepted by [`Command::validate`].
+#[cfg(test)]
+pub(crate) fn every_command() -> impl Iterator<Item = Command> {
+    use Command::*;
+
+    let bools = [false, true];
+    let charge_periods = (1..=15).filter_map(ChargePeriod::new);
+    [
+        StartReadModifyWrite,
+        EndReadModifyWrite,
+        Nop,
+        SetAddressMode(AddressMode::Page),
+        SetAddressMode(AddressMode::Column),
+        SetDisplayMode(DisplayMode::BlackOnWhite),
+        SetDisplayMode(DisplayMode::WhiteOnBlack),
+        SetCOMScanDirection(Direction::Normal),
+        SetCOMScanDirection(Direction::Inverted),
+        DisplayOnOff(DisplayState::Off),
+        DisplayOnOff(DisplayState::On),
+    ]
+    .into_iter()
+    .chain(bools.map(ForceEntireDisplay))
+    .chain(bools.map(SetSegmentReMap))
+    .chain((0..128).map(SetColumnAddress))
+    .chain((0..16).map(SetPageAddress))
+    .chain((1..=128).map(SetMultiplexRatio))
+    .chain((0..128).map(SetStartLine))
+    .chain((0..128).map(SetDisplayOffset))
+    .chain((0..=255).map(SetContrastControl))
+    .chain((0..=255).map(|bits| SetVCOMHDeselectLevel(VcomhLevel::from_bits(bits))))
+    .chain((0..16).map(|bits| SetDCDCSettings(DcDcSettings::from_bits(bits))))
+    .chain((1..=16).flat_map(|divider| {
+        (0..16).map(move |bits| SetClkDividerOscFrequency {
+            divider,
+            osc_freq: OscFrequency::from_bits(bits),
+        })
+    }))
+    .chain(charge_periods.clone().flat_map(move |discharge| {
+        core::iter::once(None)
+            .chain(charge_periods.clone().map(Some))
+            .map(move |precharge| SetChargePeriods {
+                precharge,
+                discharge,
+            })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::blocking::block_on;
+    use crate::decoder::{Decoder, Frame};
+
+    /// A transaction recorded by [`Mock`], its commands decoded.
+    #[derive(Debug, PartialEq)]
+    struct Transaction {
+        commands: Vec<Command>,
+        data: Vec<u8>,
+    }
+
+    /// A RAM access, with the address it started at.
+    #[derive(Debug, PartialEq)]
+    enum Access {
+        Read { page: u8, column: u8, len: usize },
+        Write { page: u8, column: u8, len: usize },
+    }
+
+    /// Display RAM and address counter of the controller.
+    struct Ram {
+        bytes: [[u8; 128]; 16],
+        mode: AddressMode,
+        page: u8,
+        column: u8,
+        /// Column the read-modify-write session started at.
+        rmw: Option<u8>,
+    }
+
+    impl Default for Ram {
+        fn default() -> Self {
+            Self {
+                bytes: [[0; 128]; 16],
+                mode: AddressMode::Page,
+                page: 0,
+                column: 0,
+                rmw: None,
+            }
+        }
+    }
+
+    impl Ram {
+        fn apply(&mut self, command: Command) {
+            match command {
+                Command::SetAddressMode(mode) => self.mode = mode,
+                Command::SetColumnAddress(column) => self.column = column,
+                Command::SetPageAddress(page) => self.page = page,
+                Command::StartReadModifyWrite => self.rmw = Some(self.column),
+                Command::EndReadModifyWrite => {
+                    if let Some(column) = self.rmw.take() {
+                        self.column = column;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        fn advance(&mut self) {
+            match self.mode {
+                AddressMode::Page => self.column = (self.column + 1) % 128,
+                AddressMode::Column => self.page = (self.page + 1) % 16,
+            }
+        }
+
+        fn write(&mut self, byte: u8) {
+            self.bytes[usize::from(self.page)][usize::from(self.column)] = byte;
+            self.advance();
+        }
+
+        /// Reads do not move the address in a read-modify-write session. Otherwise the dummy byte
+        /// moves it too.
+        fn read(&mut self, buf: &mut [u8]) {
+            for byte in buf.iter_mut() {
+                *byte = self.bytes[usize::from(self.page)][usize::from(self.column)];
+                if self.rmw.is_none() {
+                    self.advance();
+                }
+            }
+            if self.rmw.is_none() {
+                self.advance();
+            }
+        }
+    }
+
+    /// Records transactions, failing the `fail_at`-th one (counting from 0), and emulates the
+    /// display RAM.
+    ///
+    /// The status register reports busy for the first `busy_reads` reads. The `fail_read_at`-th RAM
+    /// read (counting from 0) fails.
+    #[derive(Default)]
+    struct Mock {
+        transactions: Vec<Transaction>,
+        fail_at: Option<usize>,
+        status: u8,
+        busy_reads: usize,
+        status_reads: usize,
+        ram: Ram,
+        accesses: Vec<Access>,
+        reads: usize,
+        fail_read_at: Option<usize>,
+    }
+
+    impl Mock {
+        fn record(
+            &mut self,
+            commands: impl Iterator<Item = u8>,
+            data: impl Iterator<Item = u8>,
+        ) -> Result<(), ()> {
+            let commands: Vec<u8> = commands.collect();
+            // only the commands before an undecodable byte are kept, lone column nibbles are
+            // not recorded
+            let commands: Vec<Command> = Decoder::commands(&commands)
+                .map_while(Result::ok)
+                .filter_map(|frame| match frame {
+                    Frame::Command(command) => Some(command),
+                    Frame::Data(_) => unreachable!(),
+                    _ => None,
+                })
+                .collect();
+            let data: Vec<u8> = data.collect();
+            let failed = self.fail_at == Some(self.transactions.len());
+            if !failed {
+                commands.iter().for_each(|&command| self.ram.apply(command));
+                if !data.is_empty() {
+                    self.accesses.push(Access::Write {
+                        page: self.ram.page,
+                        column: self.ram.column,
+                        len: data.len(),
+                    });
+                }
+                data.iter().for_each(|&byte| self.ram.write(byte));
+            }
+            self.transactions.push(Transaction { commands, data });
+            if failed {
+                Err(())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl Interface for Mock {
+        type Error = ();
+
+        async fn write_commands(
+            &mut self,
+            commands: impl Iterator<Item = u8>,
+        ) -> Result<(), Self::Error> {
+            self.record(commands, core::iter::empty())
+        }
+
+        async fn write_data(&mut self, data: impl Iterator<Item = u8>) -> Result<(), Self::Error> {
+            self.record(core::iter::empty(), data)
+        }
+
+        async fn write_commands_then_data(
+            &mut self,
+            commands: impl Iterator<Item = u8>,
+            data: impl Iterator<Item = u8>,
+        ) -> Result<(), Self::Error> {
+            self.record(commands, data)
+        }
+    }
+
+    impl ReadInterface for Mock {
+        async fn read_data(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+            self.reads += 1;
+            if self.fail_read_at == Some(self.reads - 1) {
+                return Err(());
+            }
+            self.accesses.push(Access::Read {
+                page: self.ram.page,
+                column: self.ram.column,
+                len: buf.len(),
+            });
+            self.ram.read(buf);
+            Ok(())
+        }
+
+        async fn read_status(&mut self) -> Result<u8, Self::Error> {
+            self.status_reads += 1;
+            if self.status_reads <= self.busy_reads {
+                Ok(self.status | Status::BUSY)
+            } else {
+                Ok(self.status)
+            }
+        }
+    }
+
+    /// Records the requested delays, in microseconds, without waiting.
+    #[derive(Default)]
+    struct Delay(Vec<u32>);
+
+    impl DelayNs for Delay {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.0.push(ns / 1000);
+        }
+
+        async fn delay_us(&mut self, us: u32) {
+            self.0.push(us);
+        }
+    }
+
+    /// Retries every error once, without waiting.
+    struct RetryOnce;
+
+    impl RetryPolicy for RetryOnce {
+        fn max_attempts(&self) -> u8 {
+            2
+        }
+
+        fn is_retryable<E>(&self, _error: &Error<E>) -> bool {
+            true
+        }
+
+        async fn backoff(&mut self, _attempt: u8) {}
+    }
+
+    fn transaction(commands: &[Command], data: &[u8]) -> Transaction {
+        Transaction {
+            commands: commands.to_vec(),
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn out_of_range_parameters_are_rejected() {
+        use Command::*;
+
+        let divider = |divider| SetClkDividerOscFrequency {
+            divider,
+            osc_freq: OscFrequency::Nominal,
+        };
+        for command in [
+            SetMultiplexRatio(0),
+            SetMultiplexRatio(129),
+            divider(0),
+            divider(17),
+            SetPageAddress(16),
+            SetColumnAddress(128),
+            SetStartLine(128),
+            SetDisplayOffset(128),
+        ] {
+            assert_eq!(command.validate(), Err(InvalidArgument(command)));
+            assert_eq!(command.try_encode(), Err(InvalidArgument(command)));
+
+            let mut sh1107 = Sh1107::new(Mock::default());
+            assert!(matches!(
+                block_on(sh1107.run([DisplayOnOff(DisplayState::On), command])),
+                Err(Error::InvalidArgument(InvalidArgument(c))) if c == command
+            ));
+            assert!(sh1107.release().transactions.is_empty());
+        }
+        for command in [
+            SetMultiplexRatio(1),
+            SetMultiplexRatio(128),
+            divider(1),
+            divider(16),
+            SetPageAddress(15),
+            SetColumnAddress(127),
+            SetStartLine(127),
+            SetDisplayOffset(127),
+        ] {
+            assert_eq!(command.validate(), Ok(()));
+        }
+
+        assert_eq!(ChargePeriod::new(0), None);
+        assert_eq!(ChargePeriod::new(16), None);
+        assert!(ChargePeriod::new(1).is_some());
+        assert!(ChargePeriod::new(15).is_some());
+    }
+
+    #[test]
+    fn chunks_wrap_within_the_page() {
+        use Command::*;
+
+        let mut sh1107 = Sh1107::new(Mock::default());
+        sh1107.set_max_transfer(NonZeroUsize::new(4));
+        let data: Vec<u8> = (0..10).collect();
+        block_on(sh1107.run_then_write_to_ram(
+            [SetPageAddress(2), SetColumnAddress(126)],
+            data.iter().copied(),
+        ))
+        .unwrap();
+        assert_eq!(
+            sh1107.release().transactions,
+            [
+                transaction(&[SetPageAddress(2), SetColumnAddress(126)], &data[..4]),
+                transaction(&[SetColumnAddress(2), SetPageAddress(2)], &data[4..8]),
+                transaction(&[SetColumnAddress(6), SetPageAddress(2)], &data[8..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn chunks_move_to_the_next_page_in_column_mode() {
+        use Command::*;
+
+        let mut sh1107 = Sh1107::new(Mock::default());
+        sh1107.set_max_transfer(NonZeroUsize::new(2));
+        let commands = [
+            SetAddressMode(AddressMode::Column),
+            SetColumnAddress(5),
+            SetPageAddress(14),
+        ];
+        block_on(sh1107.run_then_write_to_ram(commands, [1, 2, 3, 4, 5, 6])).unwrap();
+        assert_eq!(
+            sh1107.release().transactions,
+            [
+                transaction(&commands, &[1, 2]),
+                transaction(&[SetColumnAddress(5), SetPageAddress(0)], &[3, 4]),
+                transaction(&[SetColumnAddress(5), SetPageAddress(2)], &[5, 6]),
+            ]
+        );
+    }
+
+    #[test]
+    fn first_chunk_follows_the_current_address() {
+        use Command::*;
+
+        let mut sh1107 = Sh1107::new(Mock::default());
+        sh1107.set_max_transfer(NonZeroUsize::new(3));
+        block_on(sh1107.run([SetColumnAddress(10), SetPageAddress(3)])).unwrap();
+        block_on(sh1107.write_to_ram([1, 2, 3, 4])).unwrap();
+        assert_eq!(
+            sh1107.release().transactions[1..],
+            [
+                transaction(&[], &[1, 2, 3]),
+                transaction(&[SetColumnAddress(13), SetPageAddress(3)], &[4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_chunk_is_retried_with_its_address() {
+        use Command::*;
+
+        let mock = Mock {
+            fail_at: Some(2),
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock).with_retry(RetryOnce);
+        sh1107.set_max_transfer(NonZeroUsize::new(2));
+        block_on(sh1107.run([SetColumnAddress(10), SetPageAddress(3)])).unwrap();
+        block_on(sh1107.write_to_ram([1, 2, 3, 4, 5, 6])).unwrap();
+        assert_eq!(
+            sh1107.release().transactions[1..],
+            [
+                transaction(&[], &[1, 2]),
+                transaction(&[SetColumnAddress(12), SetPageAddress(3)], &[3, 4]),
+                transaction(&[SetColumnAddress(12), SetPageAddress(3)], &[3, 4]),
+                transaction(&[SetColumnAddress(14), SetPageAddress(3)], &[5, 6]),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_first_chunk_is_retried_with_its_address() {
+        use Command::*;
+
+        let mock = Mock {
+            fail_at: Some(1),
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock).with_retry(RetryOnce);
+        block_on(sh1107.run([SetColumnAddress(10), SetPageAddress(3)])).unwrap();
+        block_on(sh1107.write_to_ram([1, 2])).unwrap();
+        assert_eq!(
+            sh1107.release().transactions[1..],
+            [
+                transaction(&[], &[1, 2]),
+                transaction(&[SetColumnAddress(10), SetPageAddress(3)], &[1, 2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn display_state_is_sent_again_after_a_mismatch() {
+        let on = Command::DisplayOnOff(DisplayState::On);
+
+        let mut sh1107 = Sh1107::new(Mock::default());
+        block_on(sh1107.run([on])).unwrap();
+        // the controller browned out and reports being off
+        sh1107.interface_mut().status = Status::OFF;
+        assert!(matches!(
+            block_on(sh1107.verify_display_state(DisplayState::On)),
+            Err(Error::VerificationMismatch)
+        ));
+        assert_eq!(sh1107.registers().display_state(), Some(DisplayState::Off));
+        block_on(sh1107.run([on])).unwrap();
+        assert_eq!(
+            sh1107.release().transactions,
+            [transaction(&[on], &[]), transaction(&[on], &[])]
+        );
+    }
+
+    #[test]
+    fn undecodable_commands_invalidate_the_registers() {
+        let contrast = Command::SetContrastControl(0x10);
+
+        let mut sh1107 = Sh1107::new(Mock::default());
+        block_on(sh1107.run([contrast])).unwrap();
+        assert_eq!(sh1107.registers().contrast(), Some(0x10));
+        // 0xFF is not a known opcode, the contrast command after it goes unnoticed
+        block_on(sh1107.run_encoded(&[0xAF, 0xFF, 0x81, 0x20])).unwrap();
+        assert_eq!(sh1107.registers(), &Registers::UNKNOWN);
+        block_on(sh1107.run([contrast])).unwrap();
+        assert_eq!(sh1107.release().transactions.len(), 3);
+    }
+
+    #[test]
+    fn lone_column_nibbles_move_the_cursor() {
+        let mut sh1107 = Sh1107::new(Mock::default());
+        block_on(sh1107.run([Command::SetColumnAddress(0x23)])).unwrap();
+        block_on(sh1107.run_encoded(&[0x05])).unwrap();
+        assert_eq!(sh1107.cursor.column, 0x25);
+        block_on(sh1107.run_encoded(&[0x16])).unwrap();
+        assert_eq!(sh1107.cursor.column, 0x65);
+    }
+
+    #[test]
+    fn ready_immediately() {
+        let mut sh1107 = Sh1107::new(Mock::default());
+        let mut delay = Delay::default();
+        block_on(sh1107.wait_until_ready(&mut delay, 10, 100)).unwrap();
+        assert_eq!(delay.0, []);
+        assert_eq!(sh1107.release().status_reads, 1);
+    }
+
+    #[test]
+    fn busy_then_ready() {
+        let mock = Mock {
+            busy_reads: 3,
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock);
+        let mut delay = Delay::default();
+        block_on(sh1107.wait_until_ready(&mut delay, 10, 100)).unwrap();
+        assert_eq!(delay.0, [10, 10, 10]);
+        assert_eq!(sh1107.release().status_reads, 4);
+    }
+
+    #[test]
+    fn busy_wait_times_out() {
+        let mock = Mock {
+            busy_reads: usize::MAX,
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock);
+        let mut delay = Delay::default();
+        assert!(matches!(
+            block_on(sh1107.wait_until_ready(&mut delay, 30, 100)),
+            Err(Error::BusyTimeout)
+        ));
+        // 4 delays of 30 µs cover the 100 µs timeout
+        assert_eq!(delay.0, [30; 4]);
+        assert_eq!(sh1107.interface().status_reads, 5);
+
+        // without an interval, the polls are bounded by the timeout
+        let mut delay = Delay::default();
+        assert!(matches!(
+            block_on(sh1107.wait_until_ready(&mut delay, 0, 3)),
+            Err(Error::BusyTimeout)
+        ));
+        assert_eq!(delay.0, [0; 3]);
+        assert_eq!(sh1107.release().status_reads, 9);
+    }
+
+    #[test]
+    fn registers_changed_while_asleep_are_kept() {
+        use Command::*;
+
+        let dcdc = DcDcSettings {
+            enabled: true,
+            frequency: DcDcFrequency::NOMINAL,
+        };
+        let mut sh1107 = Sh1107::new(Mock::default());
+        block_on(sh1107.run([
+            SetDCDCSettings(dcdc),
+            SetContrastControl(0x10),
+            DisplayOnOff(DisplayState::On),
+        ]))
+        .unwrap();
+        block_on(sh1107.sleep()).unwrap();
+        assert!(sh1107.is_asleep());
+        block_on(sh1107.run([SetContrastControl(0x20)])).unwrap();
+        block_on(sh1107.wake()).unwrap();
+        assert!(!sh1107.is_asleep());
+        assert_eq!(sh1107.registers().contrast(), Some(0x20));
+        assert_eq!(sh1107.registers().dcdc_settings(), Some(dcdc));
+
+        let dcdc_off = DcDcSettings {
+            enabled: false,
+            ..dcdc
+        };
+        assert_eq!(
+            sh1107.release().transactions[1..],
+            [
+                transaction(
+                    &[DisplayOnOff(DisplayState::Off), SetDCDCSettings(dcdc_off)],
+                    &[]
+                ),
+                transaction(&[SetContrastControl(0x20)], &[]),
+                transaction(
+                    &[
+                        SetDCDCSettings(dcdc),
+                        SetContrastControl(0x20),
+                        DisplayOnOff(DisplayState::On)
+                    ],
+                    &[]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_dcdc_converter_is_left_alone() {
+        use Command::*;
+
+        let mut sh1107 = Sh1107::new(Mock::default());
+        block_on(sh1107.sleep()).unwrap();
+        // sleeping again does not forget what the first sleep turned off
+        block_on(sh1107.sleep()).unwrap();
+        block_on(sh1107.wake()).unwrap();
+        assert_eq!(
+            sh1107.release().transactions,
+            [
+                transaction(&[DisplayOnOff(DisplayState::Off)], &[]),
+                transaction(&[DisplayOnOff(DisplayState::On)], &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled_dcdc_converter_stays_off() {
+        use Command::*;
+
+        let dcdc = DcDcSettings {
+            enabled: false,
+            frequency: DcDcFrequency::NOMINAL,
+        };
+        let mut sh1107 = Sh1107::new(Mock::default());
+        block_on(sh1107.run([SetDCDCSettings(dcdc)])).unwrap();
+        block_on(sh1107.sleep()).unwrap();
+        block_on(sh1107.wake()).unwrap();
+        assert_eq!(
+            sh1107.release().transactions[1..],
+            [
+                transaction(&[DisplayOnOff(DisplayState::Off)], &[]),
+                transaction(
+                    &[SetDCDCSettings(dcdc), DisplayOnOff(DisplayState::On)],
+                    &[]
+                ),
+            ]
+        );
+    }
+
+    /// RAM filled with a value depending on the address of each byte.
+    fn patterned_ram() -> Ram {
+        let mut ram = Ram::default();
+        for (page, bytes) in ram.bytes.iter_mut().enumerate() {
+            for (column, byte) in bytes.iter_mut().enumerate() {
+                *byte = pattern(page as u8, column as u8);
+            }
+        }
+        ram
+    }
+
+    fn pattern(page: u8, column: u8) -> u8 {
+        page.wrapping_mul(128).wrapping_add(column) ^ page
+    }
+
+    /// Asserts that the driver's idea of the RAM address matches the controller's.
+    fn assert_cursor_matches<R, O>(sh1107: &Sh1107<Mock, R, O>) {
+        let ram = &sh1107.interface().ram;
+        assert_eq!(
+            (sh1107.cursor.page, sh1107.cursor.column),
+            (ram.page, ram.column)
+        );
+    }
+
+    #[test]
+    fn back_to_back_reads_skip_the_dummy_byte() {
+        use Command::*;
+
+        let mock = Mock {
+            ram: patterned_ram(),
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock);
+        block_on(sh1107.run([SetColumnAddress(10), SetPageAddress(3)])).unwrap();
+        let mut buf = [0; 4];
+        block_on(sh1107.read_from_ram(&mut buf)).unwrap();
+        assert_eq!(buf, [10, 11, 12, 13].map(|column| pattern(3, column)));
+        assert_cursor_matches(&sh1107);
+        block_on(sh1107.read_from_ram(&mut buf)).unwrap();
+        assert_eq!(buf, [15, 16, 17, 18].map(|column| pattern(3, column)));
+        assert_cursor_matches(&sh1107);
+
+        // a write after the reads lands where the driver expects it
+        sh1107.set_max_transfer(NonZeroUsize::new(1));
+        block_on(sh1107.write_to_ram([0xAA, 0x55])).unwrap();
+        assert_cursor_matches(&sh1107);
+        let mock = sh1107.release();
+        assert_eq!(mock.ram.bytes[3][20..22], [0xAA, 0x55]);
+        assert_eq!(
+            mock.accesses,
+            [
+                Access::Read {
+                    page: 3,
+                    column: 10,
+                    len: 4
+                },
+                Access::Read {
+                    page: 3,
+                    column: 15,
+                    len: 4
+                },
+                Access::Write {
+                    page: 3,
+                    column: 20,
+                    len: 1
+                },
+                Access::Write {
+                    page: 3,
+                    column: 21,
+                    len: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_read_is_retried_from_its_address() {
+        use Command::*;
+
+        let mock = Mock {
+            ram: patterned_ram(),
+            fail_read_at: Some(1),
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock).with_retry(RetryOnce);
+        block_on(sh1107.run([SetColumnAddress(10), SetPageAddress(3)])).unwrap();
+        let mut buf = [0; 2];
+        block_on(sh1107.read_from_ram(&mut buf)).unwrap();
+        block_on(sh1107.read_from_ram(&mut buf)).unwrap();
+        assert_eq!(buf, [13, 14].map(|column| pattern(3, column)));
+        assert_cursor_matches(&sh1107);
+        assert_eq!(
+            sh1107.release().transactions[1..],
+            [transaction(&[SetColumnAddress(13), SetPageAddress(3)], &[])]
+        );
+    }
+
+    #[test]
+    fn region_reads_address_each_line() {
+        let mock = Mock {
+            ram: patterned_ram(),
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock);
+
+        let mut buf = [0; 6];
+        block_on(sh1107.read_region(AddressMode::Page, 1..4, 126..128, &mut buf)).unwrap();
+        assert_eq!(
+            buf,
+            [(1, 126), (1, 127), (2, 126), (2, 127), (3, 126), (3, 127)]
+                .map(|(page, column)| pattern(page, column))
+        );
+        assert_cursor_matches(&sh1107);
+
+        let mut buf = [0; 4];
+        block_on(sh1107.read_region(AddressMode::Column, 14..16, 5..7, &mut buf)).unwrap();
+        assert_eq!(
+            buf,
+            [(14, 5), (15, 5), (14, 6), (15, 6)].map(|(page, column)| pattern(page, column))
+        );
+        assert_cursor_matches(&sh1107);
+
+        let accesses = sh1107.release().accesses;
+        let starts: Vec<(u8, u8)> = accesses
+            .iter()
+            .map(|access| match *access {
+                Access::Read { page, column, .. } => (page, column),
+                Access::Write { .. } => unreachable!(),
+            })
+            .collect();
+        assert_eq!(starts, [(1, 126), (2, 126), (3, 126), (14, 5), (14, 6)]);
+    }
+
+    #[test]
+    fn read_modify_write_stays_on_each_column() {
+        use Command::*;
+
+        let mock = Mock {
+            ram: patterned_ram(),
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock);
+        block_on(sh1107.read_modify_write(5, 126..128, |byte| byte ^ 0xFF)).unwrap();
+        assert_cursor_matches(&sh1107);
+        assert_eq!((sh1107.cursor.page, sh1107.cursor.column), (5, 126));
+
+        let mock = sh1107.release();
+        assert_eq!(
+            mock.ram.bytes[5][125..],
+            [pattern(5, 125), !pattern(5, 126), !pattern(5, 127)]
+        );
+        let accesses: Vec<Access> = [126, 127]
+            .into_iter()
+            .flat_map(|column| {
+                [
+                    Access::Read {
+                        page: 5,
+                        column,
+                        len: 1,
+                    },
+                    Access::Write {
+                        page: 5,
+                        column,
+                        len: 1,
+                    },
+                ]
+            })
+            .collect();
+        // the byte written back is the one read, the dummy byte does not move the column
+        assert_eq!(mock.accesses, accesses);
+        assert_eq!(
+            mock.transactions,
+            [
+                transaction(
+                    &[
+                        SetAddressMode(AddressMode::Page),
+                        SetColumnAddress(126),
+                        SetPageAddress(5),
+                        StartReadModifyWrite
+                    ],
+                    &[]
+                ),
+                transaction(&[], &[!pattern(5, 126)]),
+                transaction(&[], &[!pattern(5, 127)]),
+                transaction(&[EndReadModifyWrite], &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_modify_write_ends_on_failure() {
+        use Command::*;
+
+        // the second read fails
+        let mock = Mock {
+            ram: patterned_ram(),
+            fail_read_at: Some(1),
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock);
+        let result = block_on(sh1107.read_modify_write(5, 10..20, |byte| byte ^ 0xFF));
+        assert!(matches!(result, Err(Error::Bus(()))));
+        assert_cursor_matches(&sh1107);
+        let mock = sh1107.release();
+        assert_eq!(mock.ram.rmw, None);
+        assert_eq!(mock.ram.bytes[5][10..12], [!pattern(5, 10), pattern(5, 11)]);
+        assert_eq!(mock.transactions.len(), 3);
+        assert_eq!(
+            mock.transactions.last(),
+            Some(&transaction(&[EndReadModifyWrite], &[]))
+        );
+
+        // the second write fails
+        let mock = Mock {
+            ram: patterned_ram(),
+            fail_at: Some(2),
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock);
+        let result = block_on(sh1107.read_modify_write(5, 10..20, |byte| byte ^ 0xFF));
+        assert!(matches!(result, Err(Error::Bus(()))));
+        assert_cursor_matches(&sh1107);
+        let mock = sh1107.release();
+        assert_eq!(mock.ram.rmw, None);
+        assert_eq!(mock.ram.bytes[5][10..12], [!pattern(5, 10), pattern(5, 11)]);
+        assert_eq!(mock.transactions.len(), 4);
+        assert_eq!(
+            mock.transactions.last(),
+            Some(&transaction(&[EndReadModifyWrite], &[]))
+        );
+    }
+
+    /// I²C bus with an SH1107 answering `status` at `device`, or failing every transaction with
+    /// `error`. The address of every transaction is recorded.
+    #[derive(Default)]
+    struct Bus {
+        device: Option<(SevenBitAddress, u8)>,
+        error: Option<embedded_hal::i2c::ErrorKind>,
+        addresses: Vec<SevenBitAddress>,
+    }
+
+    impl Bus {
+        fn answer(&mut self, address: SevenBitAddress) -> Result<u8, embedded_hal::i2c::ErrorKind> {
+            use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+            self.addresses.push(address);
+            if let Some(kind) = self.error {
+                return Err(kind);
+            }
+            match self.device {
+                Some((device, status)) if device == address => Ok(status),
+                _ => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            }
+        }
+    }
+
+    impl embedded_hal_async::i2c::ErrorType for Bus {
+        type Error = embedded_hal::i2c::ErrorKind;
+    }
+
+    impl embedded_hal_async::i2c::I2c for Bus {
+        async fn transaction(
+            &mut self,
+            address: SevenBitAddress,
+            operations: &mut [embedded_hal_async::i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            let status = self.answer(address)?;
+            for operation in operations {
+                if let embedded_hal_async::i2c::Operation::Read(buf) = operation {
+                    buf.fill(status);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl WriteIter<SevenBitAddress> for Bus {
+        async fn write_iter<'a, U>(
+            &'a mut self,
+            address: SevenBitAddress,
+            _bytes: U,
+        ) -> Result<(), Self::Error>
+        where
+            U: IntoIterator<Item = u8> + 'a,
+        {
+            self.answer(address).map(|_| ())
+        }
+    }
+
+    /// Probes `bus`, returning the address found and the addresses tried.
+    fn probe(
+        bus: Bus,
+    ) -> (
+        Result<SevenBitAddress, Error<embedded_hal::i2c::ErrorKind>>,
+        Vec<u8>,
+    ) {
+        match block_on(Sh1107::probe(bus)) {
+            Ok(sh1107) => {
+                let address = sh1107.interface().address();
+                (Ok(address), sh1107.release().release().addresses)
+            }
+            Err((bus, e)) => (Err(e), bus.addresses),
+        }
+    }
+
+    #[test]
+    fn probe_finds_the_controller_at_either_address() {
+        let bus = Bus {
+            device: Some((ADDRESS_SA0_LOW, 0x07)),
+            ..Bus::default()
+        };
+        assert_eq!(probe(bus), (Ok(0x3C), std::vec![0x3C]));
+
+        // the display state does not matter
+        let bus = Bus {
+            device: Some((ADDRESS_SA0_HIGH, Status::OFF | 0x07)),
+            ..Bus::default()
+        };
+        assert_eq!(probe(bus), (Ok(0x3D), std::vec![0x3C, 0x3D]));
+    }
+
+    #[test]
+    fn probe_reports_a_missing_controller() {
+        use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+        let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+        assert_eq!(
+            probe(Bus::default()),
+            (Err(Error::NotPresent(nack)), std::vec![0x3C, 0x3D])
+        );
+    }
+
+    #[test]
+    fn probe_rejects_other_devices() {
+        // an SSD1306 reports another identifier
+        let bus = Bus {
+            device: Some((ADDRESS_SA0_LOW, 0x03)),
+            ..Bus::default()
+        };
+        assert_eq!(
+            probe(bus),
+            (
+                Err(Error::UnknownDevice(Status::from_bits(0x03))),
+                std::vec![0x3C]
             )
-            .await
+        );
     }
 
-    pub async fn read_from_ram(&mut self, buf: &mut [u8]) -> Result<(), T::Error> {
-        self.0.write_read(ADDRESS, &[0x40], buf).await
+    #[test]
+    fn probe_stops_on_bus_errors() {
+        use embedded_hal::i2c::ErrorKind;
+
+        let bus = Bus {
+            error: Some(ErrorKind::ArbitrationLoss),
+            ..Bus::default()
+        };
+        assert_eq!(
+            probe(bus),
+            (
+                Err(Error::ArbitrationLoss(ErrorKind::ArbitrationLoss)),
+                std::vec![0x3C]
+            )
+        );
+        let bus = Bus {
+            error: Some(ErrorKind::Bus),
+            ..Bus::default()
+        };
+        assert_eq!(
+            probe(bus),
+            (Err(Error::Bus(ErrorKind::Bus)), std::vec![0x3C])
+        );
     }
 
-    pub async fn is_busy(&mut self) -> Result<bool, T::Error> {
-        let mut res = 0u8;
-        self.0
-            .write_read(ADDRESS, &[0x80], core::slice::from_mut(&mut res))
-            .await?;
-        Ok((res & 0x80) != 0)
+    /// Records what the observer is told about each transaction.
+    #[derive(Default)]
+    struct Recorder(Vec<(Framing, Vec<Command>, usize, bool)>);
+
+    impl<E> Observer<E> for Recorder {
+        fn observe<C>(&mut self, transaction: observer::Transaction<C>, result: Result<(), &E>)
+        where
+            C: Iterator<Item = Command> + Clone,
+        {
+            self.0.push((
+                transaction.framing,
+                transaction.commands.collect(),
+                transaction.data_len,
+                result.is_ok(),
+            ));
+        }
     }
 
-    pub fn release(self) -> T {
-        self.0
+    #[test]
+    fn observer_sees_every_transaction() {
+        use Command::*;
+
+        let mock = Mock {
+            fail_at: Some(3),
+            ..Mock::default()
+        };
+        let mut sh1107 = Sh1107::new(mock).with_observer(Recorder::default());
+        block_on(sh1107.run([SetContrastControl(0x10)])).unwrap();
+        block_on(sh1107.write_to_ram([1, 2, 3])).unwrap();
+        block_on(sh1107.run_then_write_to_ram([SetPageAddress(1)], [4, 5])).unwrap();
+        assert!(block_on(sh1107.write_to_ram([6])).is_err());
+        block_on(sh1107.read_from_ram(&mut [0; 2])).unwrap();
+        block_on(sh1107.status()).unwrap();
+        assert_eq!(
+            sh1107.observer().0,
+            [
+                (
+                    Framing::Commands,
+                    std::vec![SetContrastControl(0x10)],
+                    0,
+                    true
+                ),
+                (Framing::Data, std::vec![], 3, true),
+                (
+                    Framing::CommandsThenData,
+                    std::vec![SetPageAddress(1)],
+                    2,
+                    true
+                ),
+                (Framing::Data, std::vec![], 1, false),
+                (Framing::ReadData, std::vec![], 2, true),
+                (Framing::ReadStatus, std::vec![], 1, true),
+            ]
+        );
     }
 }
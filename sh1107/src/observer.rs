@@ -0,0 +1,227 @@
+//! Hooks into the transactions [`Sh1107`](crate::Sh1107) puts on the bus.
+//!
+//! Observers are called once per attempt of every transaction, after it completed. The default
+//! [`NoObserver`] compiles down to nothing.
+
+use crate::Command;
+
+/// Control bytes framing a transaction on I²C.
+///
+/// Other buses carry the same transactions, with the command/data selection done by their D/C
+/// signal instead.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Framing {
+    /// `0x00` followed by the commands.
+    Commands,
+    /// `0x40` followed by the data.
+    Data,
+    /// `0x80` before each command byte, then `0x40` followed by the data.
+    CommandsThenData,
+    /// `0x40` then a read of the display RAM.
+    ReadData,
+    /// `0x80` then a read of the status register.
+    ReadStatus,
+}
+
+/// Description of a transaction, given to [`Observer::observe`].
+#[derive(Clone, Debug)]
+pub struct Transaction<C> {
+    pub framing: Framing,
+    /// Decoded commands sent by the transaction.
+    pub commands: C,
+    /// Number of display RAM bytes written or read, 1 for a status read.
+    pub data_len: usize,
+}
+
+/// Receives every transaction put on the bus along with its result.
+pub trait Observer<E> {
+    fn observe<C>(&mut self, transaction: Transaction<C>, result: Result<(), &E>)
+    where
+        C: Iterator<Item = Command> + Clone;
+}
+
+/// Observes nothing, the default.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoObserver;
+
+impl<E> Observer<E> for NoObserver {
+    #[inline(always)]
+    fn observe<C>(&mut self, _transaction: Transaction<C>, _result: Result<(), &E>)
+    where
+        C: Iterator<Item = Command> + Clone,
+    {
+    }
+}
+
+/// Counts transactions and bytes.
+///
+/// Command bytes are counted once encoded, without the framing added by the interface.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CountingObserver {
+    pub transactions: u32,
+    pub failures: u32,
+    pub command_bytes: u32,
+    pub data_bytes: u32,
+}
+
+impl CountingObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl<E> Observer<E> for CountingObserver {
+    fn observe<C>(&mut self, transaction: Transaction<C>, result: Result<(), &E>)
+    where
+        C: Iterator<Item = Command> + Clone,
+    {
+        let command_bytes: usize = transaction
+            .commands
+            .filter_map(|command| command.try_encode().ok())
+            .map(|encoded| encoded.as_bytes().len())
+            .sum();
+
+        self.transactions = self.transactions.saturating_add(1);
+        if result.is_err() {
+            self.failures = self.failures.saturating_add(1);
+        }
+        self.command_bytes = self.command_bytes.saturating_add(command_bytes as u32);
+        self.data_bytes = self.data_bytes.saturating_add(transaction.data_len as u32);
+    }
+}
+
+/// Logs every transaction through `defmt`, failed ones as warnings.
+#[cfg(feature = "defmt")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, defmt::Format)]
+pub struct DefmtObserver;
+
+#[cfg(feature = "defmt")]
+impl<E: defmt::Format> Observer<E> for DefmtObserver {
+    fn observe<C>(&mut self, transaction: Transaction<C>, result: Result<(), &E>)
+    where
+        C: Iterator<Item = Command> + Clone,
+    {
+        match result {
+            Ok(()) => defmt::debug!(
+                "sh1107: {} of {=usize} data bytes",
+                transaction.framing,
+                transaction.data_len
+            ),
+            Err(e) => defmt::warn!(
+                "sh1107: {} of {=usize} data bytes failed: {}",
+                transaction.framing,
+                transaction.data_len,
+                e
+            ),
+        }
+        for command in transaction.commands {
+            defmt::debug!("sh1107:   {}", command);
+        }
+    }
+}
+
+/// Logs every transaction through `log`, failed ones as warnings.
+#[cfg(feature = "log")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct LogObserver;
+
+#[cfg(feature = "log")]
+impl<E: core::fmt::Debug> Observer<E> for LogObserver {
+    fn observe<C>(&mut self, transaction: Transaction<C>, result: Result<(), &E>)
+    where
+        C: Iterator<Item = Command> + Clone,
+    {
+        struct Commands<C>(C);
+        impl<C: Iterator<Item = Command> + Clone> core::fmt::Debug for Commands<C> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_list().entries(self.0.clone()).finish()
+            }
+        }
+
+        let Transaction {
+            framing,
+            commands,
+            data_len,
+        } = transaction;
+        let commands = Commands(commands);
+        match result {
+            Ok(()) => log::debug!("sh1107: {framing:?} {commands:?} and {data_len} data bytes"),
+            Err(e) => log::warn!(
+                "sh1107: {framing:?} {commands:?} and {data_len} data bytes failed: {e:?}"
+            ),
+        }
+    }
+}
+
+/// Reports the result of a transaction to `observer`.
+pub(crate) fn notify<E, T>(
+    observer: &mut impl Observer<E>,
+    framing: Framing,
+    commands: impl Iterator<Item = Command> + Clone,
+    data_len: usize,
+    result: &Result<T, E>,
+) {
+    observer.observe(
+        Transaction {
+            framing,
+            commands,
+            data_len,
+        },
+        result.as_ref().map(|_| ()),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DisplayState;
+
+    #[test]
+    fn counting_observer_counts_commands_and_data_separately() {
+        let mut counter = CountingObserver::new();
+        // 2 + 1 command bytes
+        let commands = [
+            Command::SetContrastControl(0x10),
+            Command::DisplayOnOff(DisplayState::On),
+        ];
+        notify(
+            &mut counter,
+            Framing::Commands,
+            commands.into_iter(),
+            0,
+            &Ok::<(), ()>(()),
+        );
+        notify(
+            &mut counter,
+            Framing::CommandsThenData,
+            [Command::SetPageAddress(2)].into_iter(),
+            4,
+            &Err::<(), ()>(()),
+        );
+        notify(
+            &mut counter,
+            Framing::ReadStatus,
+            core::iter::empty(),
+            1,
+            &Ok::<(), ()>(()),
+        );
+        assert_eq!(
+            counter,
+            CountingObserver {
+                transactions: 3,
+                failures: 1,
+                command_bytes: 4,
+                data_bytes: 5,
+            }
+        );
+        counter.reset();
+        assert_eq!(counter, CountingObserver::default());
+    }
+}
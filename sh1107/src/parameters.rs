@@ -0,0 +1,225 @@
+//! Typed parameters for the electrical and timing configuration commands.
+
+/// Offset applied to the nominal frequency of the internal oscillator, in percent.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum OscFrequency {
+    Minus25 = 0x0,
+    Minus20 = 0x1,
+    Minus15 = 0x2,
+    Minus10 = 0x3,
+    Minus5 = 0x4,
+    #[default]
+    Nominal = 0x5,
+    Plus5 = 0x6,
+    Plus10 = 0x7,
+    Plus15 = 0x8,
+    Plus20 = 0x9,
+    Plus25 = 0xA,
+    Plus30 = 0xB,
+    Plus35 = 0xC,
+    Plus40 = 0xD,
+    Plus45 = 0xE,
+    Plus50 = 0xF,
+}
+
+impl OscFrequency {
+    const ALL: [Self; 16] = [
+        Self::Minus25,
+        Self::Minus20,
+        Self::Minus15,
+        Self::Minus10,
+        Self::Minus5,
+        Self::Nominal,
+        Self::Plus5,
+        Self::Plus10,
+        Self::Plus15,
+        Self::Plus20,
+        Self::Plus25,
+        Self::Plus30,
+        Self::Plus35,
+        Self::Plus40,
+        Self::Plus45,
+        Self::Plus50,
+    ];
+
+    /// Builds from the 4 bit register field, higher bits are ignored.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self::ALL[(bits & 0xF) as usize]
+    }
+    pub const fn bits(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns `None` unless `percent` is a multiple of 5 in [-25; 50].
+    pub const fn from_percent(percent: i8) -> Option<Self> {
+        if percent % 5 == 0 && -25 <= percent && percent <= 50 {
+            Some(Self::from_bits((percent / 5 + 5) as u8))
+        } else {
+            None
+        }
+    }
+    pub const fn percent(self) -> i8 {
+        (self as i8 - 5) * 5
+    }
+}
+
+/// Duration of a pre-charge or discharge phase, in display clock (DCLK) cycles.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChargePeriod(u8);
+
+impl ChargePeriod {
+    /// Power-on value: 2 DCLKs.
+    pub const DEFAULT: Self = Self(2);
+
+    /// Returns `None` unless `cycles` is in [1; 15].
+    pub const fn new(cycles: u8) -> Option<Self> {
+        if 1 <= cycles && cycles <= 15 {
+            Some(Self(cycles))
+        } else {
+            None
+        }
+    }
+    pub const fn cycles(self) -> u8 {
+        self.0
+    }
+}
+
+impl Default for ChargePeriod {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Common pad output voltage level at deselect stage.
+///
+/// The level is `(0.430 + bits × 0.006415) × VCC`, saturating at VCC.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VcomhLevel(u8);
+
+impl VcomhLevel {
+    /// Power-on value: 0.770 × VCC.
+    pub const DEFAULT: Self = Self(0x35);
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Closest level to `permille` thousandths of VCC.
+    ///
+    /// Returns `None` unless `permille` is in [430; 1000].
+    pub const fn from_permille(permille: u16) -> Option<Self> {
+        if 430 <= permille && permille <= 1000 {
+            let bits = ((permille as u32 - 430) * 1000 + 6415 / 2) / 6415;
+            Some(Self(bits as u8))
+        } else {
+            None
+        }
+    }
+    /// Level in thousandths of VCC.
+    pub const fn permille(self) -> u16 {
+        let permille = 430 + (self.0 as u32 * 6415 + 500) / 1000;
+        if permille > 1000 {
+            1000
+        } else {
+            permille as u16
+        }
+    }
+}
+
+impl Default for VcomhLevel {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Switching frequency setting of the built-in DC-DC converter.
+///
+/// The 8 settings select from 60% to 130% of the nominal switching frequency, in steps of 10%, as
+/// listed by the datasheet for the DC-DC control mode set command. 0 is the power-on value.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DcDcFrequency(u8);
+
+impl DcDcFrequency {
+    /// Lowest frequency: 60% of nominal.
+    pub const MIN: Self = Self(0);
+    /// Nominal frequency.
+    pub const NOMINAL: Self = Self(4);
+    /// Highest frequency: 130% of nominal.
+    pub const MAX: Self = Self(7);
+
+    /// Returns `None` unless `setting` is in [0; 7].
+    pub const fn new(setting: u8) -> Option<Self> {
+        if setting < 8 {
+            Some(Self(setting))
+        } else {
+            None
+        }
+    }
+    pub const fn setting(self) -> u8 {
+        self.0
+    }
+
+    /// Returns `None` unless `percent` is a multiple of 10 in [60; 130].
+    pub const fn from_percent(percent: u8) -> Option<Self> {
+        if percent % 10 == 0 && 60 <= percent && percent <= 130 {
+            Some(Self((percent - 60) / 10))
+        } else {
+            None
+        }
+    }
+    /// Switching frequency, in percent of the nominal frequency.
+    pub const fn percent(self) -> u8 {
+        60 + self.0 * 10
+    }
+}
+
+/// Configuration of the built-in DC-DC converter.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DcDcSettings {
+    /// When set, the converter turns on with the display.
+    pub enabled: bool,
+    pub frequency: DcDcFrequency,
+}
+
+impl DcDcSettings {
+    /// Builds from the 4 bit register field, higher bits are ignored.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self {
+            enabled: (bits & 0x1) != 0,
+            frequency: DcDcFrequency((bits >> 1) & 0x7),
+        }
+    }
+    pub const fn bits(self) -> u8 {
+        (self.frequency.0 << 1) | self.enabled as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dcdc_frequency_percent_round_trips() {
+        for setting in 0..8 {
+            let frequency = DcDcFrequency::new(setting).unwrap();
+            assert_eq!(
+                DcDcFrequency::from_percent(frequency.percent()),
+                Some(frequency)
+            );
+        }
+        assert_eq!(DcDcFrequency::MIN.percent(), 60);
+        assert_eq!(DcDcFrequency::NOMINAL.percent(), 100);
+        assert_eq!(DcDcFrequency::MAX.percent(), 130);
+        assert_eq!(DcDcFrequency::from_percent(55), None);
+        assert_eq!(DcDcFrequency::from_percent(140), None);
+    }
+}
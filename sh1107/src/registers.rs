@@ -0,0 +1,211 @@
+use crate::{
+    AddressMode, ChargePeriod, Command, DcDcSettings, Direction, DisplayMode, DisplayState,
+    FrameTiming, OscFrequency, VcomhLevel,
+};
+
+/// Shadow of the controller configuration, as set by the commands sent through [`crate::Sh1107`].
+///
+/// Every register is `None` until a command sets it, or after it was invalidated. The RAM address
+/// is not part of the shadow: it moves with every RAM access.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Registers {
+    address_mode: Option<AddressMode>,
+    display_mode: Option<DisplayMode>,
+    entire_display: Option<bool>,
+    clock: Option<(u8, OscFrequency)>,
+    multiplex_ratio: Option<u8>,
+    start_line: Option<u8>,
+    segment_remap: Option<bool>,
+    com_scan_direction: Option<Direction>,
+    display_offset: Option<u8>,
+    contrast: Option<u8>,
+    charge_periods: Option<(Option<ChargePeriod>, ChargePeriod)>,
+    vcomh_level: Option<VcomhLevel>,
+    dcdc_settings: Option<DcDcSettings>,
+    display_state: Option<DisplayState>,
+}
+
+impl Registers {
+    /// Nothing is known about the controller.
+    pub const UNKNOWN: Self = Self {
+        address_mode: None,
+        display_mode: None,
+        entire_display: None,
+        clock: None,
+        multiplex_ratio: None,
+        start_line: None,
+        segment_remap: None,
+        com_scan_direction: None,
+        display_offset: None,
+        contrast: None,
+        charge_periods: None,
+        vcomh_level: None,
+        dcdc_settings: None,
+        display_state: None,
+    };
+
+    pub fn address_mode(&self) -> Option<AddressMode> {
+        self.address_mode
+    }
+    pub fn display_mode(&self) -> Option<DisplayMode> {
+        self.display_mode
+    }
+    /// Whether every pixel is forced on, regardless of the RAM content.
+    pub fn entire_display(&self) -> Option<bool> {
+        self.entire_display
+    }
+    /// Clock divide ratio and oscillator frequency.
+    pub fn clock(&self) -> Option<(u8, OscFrequency)> {
+        self.clock
+    }
+    pub fn multiplex_ratio(&self) -> Option<u8> {
+        self.multiplex_ratio
+    }
+    pub fn start_line(&self) -> Option<u8> {
+        self.start_line
+    }
+    pub fn segment_remap(&self) -> Option<bool> {
+        self.segment_remap
+    }
+    pub fn com_scan_direction(&self) -> Option<Direction> {
+        self.com_scan_direction
+    }
+    pub fn display_offset(&self) -> Option<u8> {
+        self.display_offset
+    }
+    pub fn contrast(&self) -> Option<u8> {
+        self.contrast
+    }
+    /// Precharge and discharge periods.
+    pub fn charge_periods(&self) -> Option<(Option<ChargePeriod>, ChargePeriod)> {
+        self.charge_periods
+    }
+    /// Timing registers, when all of them are known.
+    pub fn frame_timing(&self) -> Option<FrameTiming> {
+        let (divider, osc_freq) = self.clock?;
+        let (precharge, discharge) = self.charge_periods?;
+        Some(FrameTiming {
+            divider,
+            osc_freq,
+            multiplex_ratio: self.multiplex_ratio?,
+            precharge,
+            discharge,
+        })
+    }
+    pub fn vcomh_level(&self) -> Option<VcomhLevel> {
+        self.vcomh_level
+    }
+    pub fn dcdc_settings(&self) -> Option<DcDcSettings> {
+        self.dcdc_settings
+    }
+    pub fn display_state(&self) -> Option<DisplayState> {
+        self.display_state
+    }
+
+    /// Commands setting every known register but the display state, starting with the DC-DC
+    /// converter.
+    pub(crate) fn commands(&self) -> impl Iterator<Item = Command> + Clone {
+        use Command::*;
+
+        let Self {
+            address_mode,
+            display_mode,
+            entire_display,
+            clock,
+            multiplex_ratio,
+            start_line,
+            segment_remap,
+            com_scan_direction,
+            display_offset,
+            contrast,
+            charge_periods,
+            vcomh_level,
+            dcdc_settings,
+            display_state: _,
+        } = *self;
+        [
+            dcdc_settings.map(SetDCDCSettings),
+            clock.map(|(divider, osc_freq)| SetClkDividerOscFrequency { divider, osc_freq }),
+            multiplex_ratio.map(SetMultiplexRatio),
+            display_offset.map(SetDisplayOffset),
+            start_line.map(SetStartLine),
+            segment_remap.map(SetSegmentReMap),
+            com_scan_direction.map(SetCOMScanDirection),
+            charge_periods.map(|(precharge, discharge)| SetChargePeriods {
+                precharge,
+                discharge,
+            }),
+            vcomh_level.map(SetVCOMHDeselectLevel),
+            contrast.map(SetContrastControl),
+            entire_display.map(ForceEntireDisplay),
+            display_mode.map(SetDisplayMode),
+            address_mode.map(SetAddressMode),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Whether sending `command` would leave the controller unchanged.
+    pub(crate) fn is_redundant(&self, command: Command) -> bool {
+        let mut forgotten = *self;
+        forgotten.forget(command);
+        let mut applied = *self;
+        applied.apply(command);
+        // the register is known and already holds the value
+        forgotten != *self && applied == *self
+    }
+
+    /// Records the effect of `command` once the controller acknowledged it.
+    pub(crate) fn apply(&mut self, command: Command) {
+        use Command::*;
+
+        match command {
+            SetAddressMode(mode) => self.address_mode = Some(mode),
+            SetDisplayMode(mode) => self.display_mode = Some(mode),
+            ForceEntireDisplay(force) => self.entire_display = Some(force),
+            SetClkDividerOscFrequency { divider, osc_freq } => {
+                self.clock = Some((divider, osc_freq))
+            }
+            SetMultiplexRatio(ratio) => self.multiplex_ratio = Some(ratio),
+            SetStartLine(line) => self.start_line = Some(line),
+            SetSegmentReMap(remap) => self.segment_remap = Some(remap),
+            SetCOMScanDirection(direction) => self.com_scan_direction = Some(direction),
+            SetDisplayOffset(offset) => self.display_offset = Some(offset),
+            SetContrastControl(contrast) => self.contrast = Some(contrast),
+            SetChargePeriods {
+                precharge,
+                discharge,
+            } => self.charge_periods = Some((precharge, discharge)),
+            SetVCOMHDeselectLevel(level) => self.vcomh_level = Some(level),
+            SetDCDCSettings(settings) => self.dcdc_settings = Some(settings),
+            DisplayOnOff(state) => self.display_state = Some(state),
+            SetColumnAddress(_) | SetPageAddress(_) | StartReadModifyWrite | EndReadModifyWrite
+            | Nop => {}
+        }
+    }
+
+    /// Forgets the register set by `command`, when it may or may not have reached the controller.
+    pub(crate) fn forget(&mut self, command: Command) {
+        use Command::*;
+
+        match command {
+            SetAddressMode(_) => self.address_mode = None,
+            SetDisplayMode(_) => self.display_mode = None,
+            ForceEntireDisplay(_) => self.entire_display = None,
+            SetClkDividerOscFrequency { .. } => self.clock = None,
+            SetMultiplexRatio(_) => self.multiplex_ratio = None,
+            SetStartLine(_) => self.start_line = None,
+            SetSegmentReMap(_) => self.segment_remap = None,
+            SetCOMScanDirection(_) => self.com_scan_direction = None,
+            SetDisplayOffset(_) => self.display_offset = None,
+            SetContrastControl(_) => self.contrast = None,
+            SetChargePeriods { .. } => self.charge_periods = None,
+            SetVCOMHDeselectLevel(_) => self.vcomh_level = None,
+            SetDCDCSettings(_) => self.dcdc_settings = None,
+            DisplayOnOff(_) => self.display_state = None,
+            SetColumnAddress(_) | SetPageAddress(_) | StartReadModifyWrite | EndReadModifyWrite
+            | Nop => {}
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Retry of transactions failing with transient bus errors.
+//!
+//! Retries apply to a single transaction: long RAM writes resume from the chunk that failed,
+//! re-addressing it, rather than from their start.
+
+use embedded_hal_async::delay::DelayNs;
+
+use crate::interface::Interface;
+use crate::Error;
+
+/// Decides whether and when a failed transaction is attempted again.
+#[allow(async_fn_in_trait)]
+pub trait RetryPolicy {
+    /// Maximum number of attempts of a single transaction, 1 disables retries.
+    fn max_attempts(&self) -> u8;
+
+    /// Whether `error` may go away by retrying.
+    fn is_retryable<E>(&self, error: &Error<E>) -> bool;
+
+    /// Waits before the `attempt`-th attempt, starting at 2 for the first retry.
+    async fn backoff(&mut self, attempt: u8);
+}
+
+/// Reports every error as is, the default.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoRetry;
+
+impl RetryPolicy for NoRetry {
+    fn max_attempts(&self) -> u8 {
+        1
+    }
+
+    fn is_retryable<E>(&self, _error: &Error<E>) -> bool {
+        false
+    }
+
+    async fn backoff(&mut self, _attempt: u8) {}
+}
+
+/// Classes of bus errors considered transient.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Retryable {
+    /// [`Error::NotPresent`], a missing acknowledge.
+    pub not_present: bool,
+    /// [`Error::ArbitrationLoss`].
+    pub arbitration_loss: bool,
+    /// [`Error::Bus`].
+    pub bus: bool,
+}
+
+impl Retryable {
+    /// Missing acknowledges and arbitration losses, as seen on a bus shared with other devices.
+    pub const TRANSIENT: Self = Self {
+        not_present: true,
+        arbitration_loss: true,
+        bus: false,
+    };
+
+    pub fn matches<E>(&self, error: &Error<E>) -> bool {
+        match error {
+            Error::NotPresent(_) => self.not_present,
+            Error::ArbitrationLoss(_) => self.arbitration_loss,
+            Error::Bus(_) => self.bus,
+            _ => false,
+        }
+    }
+}
+
+impl Default for Retryable {
+    fn default() -> Self {
+        Self::TRANSIENT
+    }
+}
+
+/// Retries up to `max_attempts` times, doubling the delay between attempts.
+pub struct Backoff<D> {
+    delay: D,
+    max_attempts: u8,
+    initial_delay_us: u32,
+    retryable: Retryable,
+}
+
+impl<D: DelayNs> Backoff<D> {
+    /// Retries [`Retryable::TRANSIENT`] errors, waiting `initial_delay_us` before the first retry.
+    pub fn new(delay: D, max_attempts: u8, initial_delay_us: u32) -> Self {
+        Self {
+            delay,
+            max_attempts,
+            initial_delay_us,
+            retryable: Retryable::TRANSIENT,
+        }
+    }
+
+    pub fn retry_on(self, retryable: Retryable) -> Self {
+        Self { retryable, ..self }
+    }
+
+    pub fn release(self) -> D {
+        self.delay
+    }
+}
+
+impl<D: DelayNs> RetryPolicy for Backoff<D> {
+    fn max_attempts(&self) -> u8 {
+        self.max_attempts
+    }
+
+    fn is_retryable<E>(&self, error: &Error<E>) -> bool {
+        self.retryable.matches(error)
+    }
+
+    async fn backoff(&mut self, attempt: u8) {
+        let factor = 1u32
+            .checked_shl(u32::from(attempt.saturating_sub(2)))
+            .unwrap_or(u32::MAX);
+        self.delay
+            .delay_us(self.initial_delay_us.saturating_mul(factor))
+            .await;
+    }
+}
+
+/// Runs `transaction` until it succeeds or `policy` gives up.
+///
+/// `transaction` is given the attempt number, starting at 1.
+pub(crate) async fn retry<DI, R, T>(
+    interface: &mut DI,
+    policy: &mut R,
+    mut transaction: impl AsyncFnMut(&mut DI, u8) -> Result<T, DI::Error>,
+) -> Result<T, Error<DI::Error>>
+where
+    DI: Interface,
+    R: RetryPolicy,
+{
+    let mut attempt = 1;
+    loop {
+        match transaction(interface, attempt).await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                let e = DI::classify(e);
+                if attempt >= policy.max_attempts() || !policy.is_retryable(&e) {
+                    return Err(e);
+                }
+                attempt += 1;
+                policy.backoff(attempt).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+
+    use super::*;
+    use crate::blocking::block_on;
+
+    /// Interface classifying its errors as I²C errors, nothing is sent.
+    struct I2cErrors;
+
+    impl Interface for I2cErrors {
+        type Error = ErrorKind;
+
+        fn classify(error: Self::Error) -> Error<Self::Error> {
+            Error::from_i2c(error)
+        }
+
+        async fn write_commands(
+            &mut self,
+            _commands: impl Iterator<Item = u8>,
+        ) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn write_data(&mut self, _data: impl Iterator<Item = u8>) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    /// Records the requested delays, in microseconds, without waiting.
+    #[derive(Default)]
+    struct Delay(Vec<u32>);
+
+    impl DelayNs for Delay {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.0.push(ns / 1000);
+        }
+
+        async fn delay_us(&mut self, us: u32) {
+            self.0.push(us);
+        }
+    }
+
+    const KINDS: [ErrorKind; 6] = [
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data),
+        ErrorKind::ArbitrationLoss,
+        ErrorKind::Bus,
+        ErrorKind::Overrun,
+    ];
+
+    /// Runs a transaction always failing with `kind`, returning the number of attempts made and
+    /// the error reported.
+    fn attempts(policy: &mut impl RetryPolicy, kind: ErrorKind) -> (u8, Error<ErrorKind>) {
+        let mut attempts = 0;
+        let result = block_on(retry(&mut I2cErrors, policy, async |_, attempt| {
+            attempts = attempt;
+            Err::<(), _>(kind)
+        }));
+        (attempts, result.unwrap_err())
+    }
+
+    #[test]
+    fn i2c_errors_are_classified() {
+        let [address, unknown, data, arbitration, bus, overrun] = KINDS;
+        assert_eq!(Error::from_i2c(address), Error::NotPresent(address));
+        assert_eq!(Error::from_i2c(unknown), Error::NotPresent(unknown));
+        assert_eq!(Error::from_i2c(data), Error::Bus(data));
+        assert_eq!(
+            Error::from_i2c(arbitration),
+            Error::ArbitrationLoss(arbitration)
+        );
+        assert_eq!(Error::from_i2c(bus), Error::Bus(bus));
+        assert_eq!(Error::from_i2c(overrun), Error::Bus(overrun));
+    }
+
+    #[test]
+    fn backoff_retries_transient_errors() {
+        // only missing acknowledges of the address and arbitration losses are transient
+        for (kind, retried) in KINDS
+            .into_iter()
+            .zip([true, true, false, true, false, false])
+        {
+            let mut policy = Backoff::new(Delay::default(), 4, 100);
+            let (attempts, error) = attempts(&mut policy, kind);
+            assert_eq!(error, Error::from_i2c(kind));
+            let delays = policy.release().0;
+            if retried {
+                assert_eq!(
+                    (attempts, &delays[..]),
+                    (4, &[100, 200, 400][..]),
+                    "{kind:?}"
+                );
+            } else {
+                assert_eq!((attempts, &delays[..]), (1, &[][..]), "{kind:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn backoff_can_retry_other_errors() {
+        let retryable = Retryable {
+            bus: true,
+            ..Retryable::TRANSIENT
+        };
+        let mut policy = Backoff::new(Delay::default(), 2, 10).retry_on(retryable);
+        assert_eq!(
+            attempts(&mut policy, ErrorKind::Overrun),
+            (2, Error::Bus(ErrorKind::Overrun))
+        );
+    }
+
+    #[test]
+    fn no_retry_never_retries() {
+        for kind in KINDS {
+            assert_eq!(attempts(&mut NoRetry, kind), (1, Error::from_i2c(kind)));
+        }
+    }
+}
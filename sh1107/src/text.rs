@@ -0,0 +1,342 @@
+//! Text syntax of [`Command`] and of its parameters.
+//!
+//! A command is written as its name followed by its parameters, separated by whitespace:
+//!
+//! - `column-address <0-127>`, `page-address <0-15>`
+//! - `address-mode <page|column>`
+//! - `display-mode <black-on-white|white-on-black>`
+//! - `force-entire-display <true|false>`
+//! - `clock <divider> <oscillator offset in percent>`, for instance `clock 2 +0`
+//! - `multiplex-ratio <1-128>`, `start-line <0-127>`, `display-offset <0-127>`
+//! - `segment-remap <true|false>`
+//! - `com-scan-direction <normal|inverted>`
+//! - `contrast <0-255>`
+//! - `charge-periods <precharge cycles|none> <discharge cycles>`
+//! - `vcomh-level <register value>`
+//! - `dcdc-settings <enabled: true|false> <frequency setting>`
+//! - `display <on|off>`
+//! - `start-read-modify-write`, `end-read-modify-write`, `nop`
+//!
+//! Formatting then parsing a command gives back the same command. Like [`Command`] itself,
+//! parsing does not check that the parameters are accepted by the controller, see
+//! [`Command::validate`].
+
+use core::fmt;
+use core::str::{FromStr, SplitWhitespace};
+
+use crate::{
+    AddressMode, ChargePeriod, Command, DcDcFrequency, DcDcSettings, Direction, DisplayMode,
+    DisplayState, OscFrequency, VcomhLevel,
+};
+
+/// Error returned when parsing a [`Command`] or one of its parameters.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ParseError {
+    UnknownCommand,
+    MissingParameter,
+    InvalidParameter,
+    TrailingParameter,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::UnknownCommand => "unknown command",
+            Self::MissingParameter => "missing command parameter",
+            Self::InvalidParameter => "invalid command parameter",
+            Self::TrailingParameter => "too many command parameters",
+        })
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+macro_rules! keywords {
+    ($ty:ty { $($variant:ident => $text:literal),* $(,)? }) => {
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(match self {
+                    $(Self::$variant => $text),*
+                })
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<Self, ParseError> {
+                match s {
+                    $($text => Ok(Self::$variant),)*
+                    _ => Err(ParseError::InvalidParameter),
+                }
+            }
+        }
+    };
+}
+
+keywords!(AddressMode {
+    Page => "page",
+    Column => "column",
+});
+keywords!(DisplayMode {
+    BlackOnWhite => "black-on-white",
+    WhiteOnBlack => "white-on-black",
+});
+keywords!(Direction {
+    Normal => "normal",
+    Inverted => "inverted",
+});
+keywords!(DisplayState {
+    Off => "off",
+    On => "on",
+});
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Command::*;
+
+        match *self {
+            SetColumnAddress(column) => write!(f, "column-address {column}"),
+            SetAddressMode(mode) => write!(f, "address-mode {mode}"),
+            SetDisplayMode(mode) => write!(f, "display-mode {mode}"),
+            ForceEntireDisplay(force) => write!(f, "force-entire-display {force}"),
+            SetClkDividerOscFrequency { divider, osc_freq } => {
+                write!(f, "clock {divider} {:+}", osc_freq.percent())
+            }
+            SetMultiplexRatio(ratio) => write!(f, "multiplex-ratio {ratio}"),
+            SetStartLine(line) => write!(f, "start-line {line}"),
+            SetSegmentReMap(remap) => write!(f, "segment-remap {remap}"),
+            SetCOMScanDirection(direction) => write!(f, "com-scan-direction {direction}"),
+            SetDisplayOffset(offset) => write!(f, "display-offset {offset}"),
+            SetContrastControl(contrast) => write!(f, "contrast {contrast}"),
+            SetChargePeriods {
+                precharge,
+                discharge,
+            } => {
+                f.write_str("charge-periods ")?;
+                match precharge {
+                    Some(precharge) => write!(f, "{}", precharge.cycles())?,
+                    None => f.write_str("none")?,
+                }
+                write!(f, " {}", discharge.cycles())
+            }
+            SetVCOMHDeselectLevel(level) => write!(f, "vcomh-level {}", level.bits()),
+            SetDCDCSettings(settings) => write!(
+                f,
+                "dcdc-settings {} {}",
+                settings.enabled,
+                settings.frequency.setting()
+            ),
+            DisplayOnOff(state) => write!(f, "display {state}"),
+            SetPageAddress(page) => write!(f, "page-address {page}"),
+            StartReadModifyWrite => f.write_str("start-read-modify-write"),
+            EndReadModifyWrite => f.write_str("end-read-modify-write"),
+            Nop => f.write_str("nop"),
+        }
+    }
+}
+
+/// Parameters following the name of a command.
+struct Parameters<'a>(SplitWhitespace<'a>);
+
+impl<'a> Parameters<'a> {
+    fn word(&mut self) -> Result<&'a str, ParseError> {
+        self.0.next().ok_or(ParseError::MissingParameter)
+    }
+
+    fn next<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        self.word()?
+            .parse()
+            .map_err(|_| ParseError::InvalidParameter)
+    }
+}
+
+fn charge_period(cycles: &str) -> Result<ChargePeriod, ParseError> {
+    cycles
+        .parse()
+        .ok()
+        .and_then(ChargePeriod::new)
+        .ok_or(ParseError::InvalidParameter)
+}
+
+impl FromStr for Command {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        use Command::*;
+
+        let mut words = s.split_whitespace();
+        let name = words.next().ok_or(ParseError::UnknownCommand)?;
+        let mut params = Parameters(words);
+        let command = match name {
+            "column-address" => SetColumnAddress(params.next()?),
+            "address-mode" => SetAddressMode(params.next()?),
+            "display-mode" => SetDisplayMode(params.next()?),
+            "force-entire-display" => ForceEntireDisplay(params.next()?),
+            "clock" => SetClkDividerOscFrequency {
+                divider: params.next()?,
+                osc_freq: OscFrequency::from_percent(params.next()?)
+                    .ok_or(ParseError::InvalidParameter)?,
+            },
+            "multiplex-ratio" => SetMultiplexRatio(params.next()?),
+            "start-line" => SetStartLine(params.next()?),
+            "segment-remap" => SetSegmentReMap(params.next()?),
+            "com-scan-direction" => SetCOMScanDirection(params.next()?),
+            "display-offset" => SetDisplayOffset(params.next()?),
+            "contrast" => SetContrastControl(params.next()?),
+            "charge-periods" => SetChargePeriods {
+                precharge: match params.word()? {
+                    "none" => None,
+                    cycles => Some(charge_period(cycles)?),
+                },
+                discharge: charge_period(params.word()?)?,
+            },
+            "vcomh-level" => SetVCOMHDeselectLevel(VcomhLevel::from_bits(params.next()?)),
+            "dcdc-settings" => SetDCDCSettings(DcDcSettings {
+                enabled: params.next()?,
+                frequency: DcDcFrequency::new(params.next()?)
+                    .ok_or(ParseError::InvalidParameter)?,
+            }),
+            "display" => DisplayOnOff(params.next()?),
+            "page-address" => SetPageAddress(params.next()?),
+            "start-read-modify-write" => StartReadModifyWrite,
+            "end-read-modify-write" => EndReadModifyWrite,
+            "nop" => Nop,
+            _ => return Err(ParseError::UnknownCommand),
+        };
+        match params.0.next() {
+            Some(_) => Err(ParseError::TrailingParameter),
+            None => Ok(command),
+        }
+    }
+}
+
+/// Commands and their parameters are (de)serialized as their text syntax.
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::{self, Deserialize, Deserializer, Visitor};
+    use serde::ser::{Serialize, Serializer};
+
+    use crate::{AddressMode, Command, Direction, DisplayMode, DisplayState};
+
+    struct TextVisitor<T>(PhantomData<T>);
+
+    impl<T: core::str::FromStr<Err = super::ParseError>> Visitor<'_> for TextVisitor<T> {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a command or parameter in its text syntax")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+            v.parse().map_err(E::custom)
+        }
+    }
+
+    macro_rules! text_serde {
+        ($($ty:ty),*) => {$(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserializer.deserialize_str(TextVisitor(PhantomData))
+                }
+            }
+        )*};
+    }
+
+    text_serde!(Command, AddressMode, DisplayMode, Direction, DisplayState);
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn every_command_round_trips() {
+        for command in crate::every_command() {
+            assert_eq!(command.to_string().parse(), Ok(command), "{command}");
+        }
+    }
+
+    #[test]
+    fn keywords_round_trip() {
+        for mode in [AddressMode::Page, AddressMode::Column] {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+        }
+        for mode in [DisplayMode::BlackOnWhite, DisplayMode::WhiteOnBlack] {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+        }
+        for direction in [Direction::Normal, Direction::Inverted] {
+            assert_eq!(direction.to_string().parse(), Ok(direction));
+        }
+        for state in [DisplayState::Off, DisplayState::On] {
+            assert_eq!(state.to_string().parse(), Ok(state));
+        }
+        assert_eq!(
+            "sideways".parse::<Direction>(),
+            Err(ParseError::InvalidParameter)
+        );
+    }
+
+    #[test]
+    fn parses_the_documented_examples() {
+        assert_eq!("contrast 128".parse(), Ok(Command::SetContrastControl(128)));
+        assert_eq!(
+            "  clock  2   +0 ".parse(),
+            Ok(Command::SetClkDividerOscFrequency {
+                divider: 2,
+                osc_freq: OscFrequency::Nominal,
+            })
+        );
+        assert_eq!(
+            "charge-periods none 2".parse(),
+            Ok(Command::SetChargePeriods {
+                precharge: None,
+                discharge: ChargePeriod::DEFAULT,
+            })
+        );
+        assert_eq!(
+            "dcdc-settings true 7".parse(),
+            Ok(Command::SetDCDCSettings(DcDcSettings {
+                enabled: true,
+                frequency: DcDcFrequency::MAX,
+            }))
+        );
+        assert_eq!(
+            "display on".parse(),
+            Ok(Command::DisplayOnOff(DisplayState::On))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = [
+            ("", ParseError::UnknownCommand),
+            ("brightness 3", ParseError::UnknownCommand),
+            ("contrast", ParseError::MissingParameter),
+            ("clock 2", ParseError::MissingParameter),
+            ("contrast 1 2", ParseError::TrailingParameter),
+            ("nop nop", ParseError::TrailingParameter),
+            ("clock 2 7", ParseError::InvalidParameter),
+            ("contrast 256", ParseError::InvalidParameter),
+            ("charge-periods 0 2", ParseError::InvalidParameter),
+            ("dcdc-settings yes 7", ParseError::InvalidParameter),
+        ];
+        for (text, error) in cases {
+            assert_eq!(text.parse::<Command>(), Err(error), "{text:?}");
+        }
+    }
+}
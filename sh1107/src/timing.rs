@@ -0,0 +1,191 @@
+//! Frame rate resulting from the timing configuration.
+//!
+//! The datasheet gives the frame frequency in its description of the oscillator circuit and
+//! display timing generator: `Ffrm = Fosc / (D × K × MUX)`, D being the display clock divide ratio,
+//! K the number of DCLKs per row and MUX the multiplex ratio.
+
+use crate::{ChargePeriod, Command, OscFrequency};
+
+/// Typical frequency of the internal oscillator at its nominal setting, `Fosc` in the AC
+/// characteristics of the datasheet.
+pub const NOMINAL_OSC_HZ: u32 = 370_000;
+
+/// DCLKs spent driving each row, on top of the charge periods: K is `precharge + discharge + 50`,
+/// 54 at power-on.
+const ROW_DRIVE_CYCLES: u32 = 50;
+
+/// Registers setting the frame rate.
+///
+/// A frame lasts `divider × K × multiplex_ratio` oscillator cycles, where K is the pre-charge and
+/// discharge periods plus 50 DCLKs. Rates are estimates: the oscillator is only specified within
+/// a tolerance and drifts with the supply voltage and the temperature.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FrameTiming {
+    /// Display clock divide ratio in [1; 16].
+    pub divider: u8,
+    pub osc_freq: OscFrequency,
+    /// Multiplex ratio in [1; 128].
+    pub multiplex_ratio: u8,
+    pub precharge: Option<ChargePeriod>,
+    pub discharge: ChargePeriod,
+}
+
+impl FrameTiming {
+    /// Power-on values.
+    pub const DEFAULT: Self = Self {
+        divider: 1,
+        osc_freq: OscFrequency::Nominal,
+        multiplex_ratio: 128,
+        precharge: Some(ChargePeriod::DEFAULT),
+        discharge: ChargePeriod::DEFAULT,
+    };
+
+    /// Estimated frame rate, in thousandths of Hz.
+    ///
+    /// Returns `None` if the divider or the multiplex ratio is out of range.
+    pub const fn frame_rate_millihertz(&self) -> Option<u32> {
+        if self.divider < 1 || self.divider > 16 {
+            return None;
+        }
+        if self.multiplex_ratio < 1 || self.multiplex_ratio > 128 {
+            return None;
+        }
+        let precharge = match self.precharge {
+            Some(precharge) => precharge.cycles() as u32,
+            None => 0,
+        };
+        let row_cycles = precharge + self.discharge.cycles() as u32 + ROW_DRIVE_CYCLES;
+        let frame_cycles = self.divider as u64 * row_cycles as u64 * self.multiplex_ratio as u64;
+        let osc_millihertz =
+            NOMINAL_OSC_HZ as u64 * (100 + self.osc_freq.percent() as i64) as u64 * 10;
+        Some(((osc_millihertz + frame_cycles / 2) / frame_cycles) as u32)
+    }
+
+    /// Divider and oscillator setting giving the frame rate closest to `millihertz`.
+    ///
+    /// The multiplex ratio and the charge periods are kept, they are tied to the panel. Returns
+    /// `None` if the multiplex ratio is out of range.
+    pub fn closest_to(&self, millihertz: u32) -> Option<Self> {
+        let mut best: Option<(Self, u32)> = None;
+        for divider in 1..=16 {
+            for bits in 0..16 {
+                let candidate = Self {
+                    divider,
+                    osc_freq: OscFrequency::from_bits(bits),
+                    ..*self
+                };
+                let error = candidate.frame_rate_millihertz()?.abs_diff(millihertz);
+                if best.is_none_or(|(_, best_error)| error < best_error) {
+                    best = Some((candidate, error));
+                }
+            }
+        }
+        best.map(|(timing, _)| timing)
+    }
+
+    /// Commands applying this timing.
+    pub fn commands(&self) -> [Command; 3] {
+        [
+            Command::SetClkDividerOscFrequency {
+                divider: self.divider,
+                osc_freq: self.osc_freq,
+            },
+            Command::SetMultiplexRatio(self.multiplex_ratio),
+            Command::SetChargePeriods {
+                precharge: self.precharge,
+                discharge: self.discharge,
+            },
+        ]
+    }
+}
+
+impl Default for FrameTiming {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_frame_rate() {
+        // 370 kHz / (1 × 54 × 128)
+        assert_eq!(FrameTiming::DEFAULT.frame_rate_millihertz(), Some(53_530));
+        // halving the multiplex ratio and the clock gives the same rate
+        let timing = FrameTiming {
+            divider: 2,
+            multiplex_ratio: 64,
+            ..FrameTiming::DEFAULT
+        };
+        assert_eq!(timing.frame_rate_millihertz(), Some(53_530));
+        // without pre-charge, K is 52
+        let timing = FrameTiming {
+            precharge: None,
+            ..FrameTiming::DEFAULT
+        };
+        assert_eq!(timing.frame_rate_millihertz(), Some(55_589));
+        // 555 kHz / (16 × 80 × 1)
+        let timing = FrameTiming {
+            divider: 16,
+            osc_freq: OscFrequency::Plus50,
+            multiplex_ratio: 1,
+            precharge: ChargePeriod::new(15),
+            discharge: ChargePeriod::new(15).unwrap(),
+        };
+        assert_eq!(timing.frame_rate_millihertz(), Some(433_594));
+    }
+
+    #[test]
+    fn out_of_range_timing_has_no_rate() {
+        for (divider, multiplex_ratio) in [(0, 64), (17, 64), (1, 0), (1, 129)] {
+            let timing = FrameTiming {
+                divider,
+                multiplex_ratio,
+                ..FrameTiming::DEFAULT
+            };
+            assert_eq!(timing.frame_rate_millihertz(), None);
+            // the divider is picked by the search, the multiplex ratio is kept
+            let valid_ratio = (1..=128).contains(&multiplex_ratio);
+            assert_eq!(timing.closest_to(60_000).is_some(), valid_ratio);
+        }
+    }
+
+    #[test]
+    fn closest_to_picks_the_nearest_setting() {
+        // K is 54 with the default charge periods: a frame lasts 3456 × D oscillator cycles
+        let base = FrameTiming {
+            multiplex_ratio: 64,
+            ..FrameTiming::DEFAULT
+        };
+        let expected = |divider, osc_freq| FrameTiming {
+            divider,
+            osc_freq,
+            ..base
+        };
+        for (target, divider, osc_freq, rate) in [
+            // 60 Hz needs 414.72 kHz with D = 2: 407 kHz (+10%) gives 58.88 Hz, 425.5 kHz (+15%)
+            // 61.56 Hz, and other dividers are out of the oscillator range
+            (60_000, 2, OscFrequency::Plus10, 58_883),
+            // 100 Hz needs 345.6 kHz with D = 1: 351.5 kHz (-5%) gives 101.71 Hz
+            (100_000, 1, OscFrequency::Minus5, 101_707),
+            // 24 Hz needs 331.78 kHz with D = 4: 333 kHz (-10%) gives 24.09 Hz, while D = 5 would
+            // need 414.72 kHz, 407 kHz giving 23.55 Hz
+            (24_000, 4, OscFrequency::Minus10, 24_089),
+            // the slowest and fastest settings
+            (0, 16, OscFrequency::Minus25, 5_018),
+            (1_000_000, 1, OscFrequency::Plus50, 160_590),
+        ] {
+            let closest = base.closest_to(target).unwrap();
+            assert_eq!(closest, expected(divider, osc_freq), "{target}");
+            assert_eq!(closest.frame_rate_millihertz(), Some(rate), "{target}");
+        }
+        let rate = FrameTiming::DEFAULT.frame_rate_millihertz().unwrap();
+        assert_eq!(
+            FrameTiming::DEFAULT.closest_to(rate),
+            Some(FrameTiming::DEFAULT)
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Waiting for the controller to be ready before writing to it.
+
+use embedded_hal_async::delay::DelayNs;
+
+use crate::interface::ReadInterface;
+use crate::observer::Observer;
+use crate::retry::RetryPolicy;
+use crate::{Error, Sh1107};
+
+/// Decides whether to wait for the controller to be ready before a frame is written.
+#[allow(async_fn_in_trait)]
+pub trait WaitReady {
+    async fn wait_ready<DI, R, O>(
+        &mut self,
+        sh1107: &mut Sh1107<DI, R, O>,
+    ) -> Result<(), Error<DI::Error>>
+    where
+        DI: ReadInterface,
+        R: RetryPolicy,
+        O: Observer<DI::Error>;
+}
+
+/// Starts writing right away, the default.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoWait;
+
+impl WaitReady for NoWait {
+    async fn wait_ready<DI, R, O>(
+        &mut self,
+        _sh1107: &mut Sh1107<DI, R, O>,
+    ) -> Result<(), Error<DI::Error>>
+    where
+        DI: ReadInterface,
+        R: RetryPolicy,
+        O: Observer<DI::Error>,
+    {
+        Ok(())
+    }
+}
+
+/// Waits with [`Sh1107::wait_until_ready`].
+pub struct BusyWait<D> {
+    delay: D,
+    interval_us: u32,
+    timeout_us: u32,
+}
+
+impl<D: DelayNs> BusyWait<D> {
+    pub fn new(delay: D, interval_us: u32, timeout_us: u32) -> Self {
+        Self {
+            delay,
+            interval_us,
+            timeout_us,
+        }
+    }
+
+    pub fn release(self) -> D {
+        self.delay
+    }
+}
+
+impl<D: DelayNs> WaitReady for BusyWait<D> {
+    async fn wait_ready<DI, R, O>(
+        &mut self,
+        sh1107: &mut Sh1107<DI, R, O>,
+    ) -> Result<(), Error<DI::Error>>
+    where
+        DI: ReadInterface,
+        R: RetryPolicy,
+        O: Observer<DI::Error>,
+    {
+        sh1107
+            .wait_until_ready(&mut self.delay, self.interval_us, self.timeout_us)
+            .await
+    }
+}